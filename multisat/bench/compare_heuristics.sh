@@ -0,0 +1,29 @@
+#!/bin/bash
+# Runs every bundled instance with each decision heuristic through the bench
+# binary, which verifies each result, and compares every heuristic with the
+# first one. The tables go to $OUT/<heuristic>.csv. Exits with 1 if a
+# heuristic reported a wrong model.
+#
+# usage: bench/compare_heuristics.sh [timeout seconds] [heuristic...]
+# run from the multisat directory after `cargo build --release`
+BENCH=${BENCH:-target/release/bench}
+DIR=$(dirname "$0")
+OUT=${OUT:-$DIR/results}
+TIMEOUT=${1:-60}
+[ $# -gt 0 ] && shift
+HEURISTICS=${@:-vsids vmtf lrb chb}
+
+mkdir -p "$OUT" || exit 1
+status=0
+base=
+for h in $HEURISTICS; do
+    echo "c heuristic $h"
+    "$BENCH" run "$DIR/instances" --timeout "$TIMEOUT" --out "$OUT/$h.csv" -- --heuristic "$h" || status=1
+    if [ -z "$base" ]; then
+        base=$h
+    elif [ -f "$OUT/$base.csv" ] && [ -f "$OUT/$h.csv" ]; then
+        echo "c $h against $base"
+        "$BENCH" compare "$OUT/$base.csv" "$OUT/$h.csv"
+    fi
+done
+exit $status
@@ -0,0 +1,21 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use multisat::differential::{differential_check, random_options, Instance};
+use multisat::ds::Rng;
+
+// Run with `cargo fuzz run differential` from the multisat directory.
+//
+// The first byte picks the search settings and the preprocessing mode, the
+// rest is decoded into a formula small enough for the truth table
+fuzz_target!(|data: &[u8]| {
+    let Some((&settings, formula)) = data.split_first() else {
+        return;
+    };
+    let options = random_options(&mut Rng::new(settings as u64));
+    let eliminate = settings & 1 == 1;
+    let instance = Instance::from_bytes(formula);
+    if let Err(e) = differential_check(&instance, &options, eliminate) {
+        panic!("{}\n{}", e, instance.to_dimacs());
+    }
+});
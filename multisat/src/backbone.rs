@@ -0,0 +1,159 @@
+use std::path::Path;
+use std::process::exit;
+
+use rustc_hash::FxHashMap;
+
+use crate::ds::*;
+use crate::parse::parse_cnf;
+use crate::verify::json_quote;
+use crate::{solve_with_assumptions, CNFStatus};
+
+pub const DEFAULT_CHUNK_SIZE: usize = 8;
+
+#[derive(Debug, PartialEq)]
+pub struct Backbone {
+    /// Literals true in every model, sorted by variable
+    pub literals: Vec<Literal>,
+    pub solver_calls: usize,
+}
+
+/// Drops every candidate that takes a different value in `model`
+fn filter_candidates(candidates: &mut Vec<Literal>, model: &[i32]) {
+    candidates.retain(|lit| model[lit.var() - 1].is_positive() == lit.sign());
+}
+
+/// Computes the backbone of the formula in `solver_state`, or None if it is UNSAT.
+///
+/// Every literal of the first model is a candidate. Candidates are tested
+/// `chunk_size` at a time by asking for a model that falsifies at least one of
+/// them: UNSAT proves the whole chunk, while a model rules out every candidate it
+/// flips. A chunk clause is guarded by a fresh selector variable that is assumed
+/// for the call and disabled afterwards. The state must have been set up with
+/// `preprocess_keep_models`.
+pub fn compute_backbone(solver_state: &mut SolverState, chunk_size: usize) -> Option<Backbone> {
+    assert!(chunk_size > 0);
+    let num_vars = solver_state.num_variables;
+    let mut solver_calls = 1;
+    let mut candidates: Vec<Literal> = match solve_with_assumptions(solver_state, &[]) {
+        CNFStatus::UNSAT => return None,
+        CNFStatus::SAT { model } => model[..num_vars]
+            .iter()
+            .map(|&n| Literal::from(n))
+            .collect(),
+    };
+    let mut backbone: Vec<Literal> = Vec::new();
+
+    loop {
+        solver_state.backtrack_to_root();
+        // Anything fixed at level 0 is implied by the formula
+        candidates.retain(|lit| {
+            if solver_state.assig.get(&lit.var()).is_some() {
+                debug_assert!(literal_satisfied(lit, &solver_state.assig));
+                backbone.push(*lit);
+                false
+            } else {
+                true
+            }
+        });
+        if candidates.is_empty() {
+            break;
+        }
+
+        let chunk: Vec<Literal> = candidates.iter().take(chunk_size).cloned().collect();
+        let selector = if chunk.len() > 1 {
+            let selector = Literal::new(solver_state.new_var(), true);
+            let mut guarded: Vec<Literal> = chunk.iter().map(|lit| lit.invert()).collect();
+            guarded.push(selector.invert());
+            solver_state.add_clause_at_root(guarded);
+            Some(selector)
+        } else {
+            None
+        };
+        let assumptions = match selector {
+            Some(selector) => vec![selector],
+            None => vec![chunk[0].invert()],
+        };
+
+        solver_calls += 1;
+        match solve_with_assumptions(solver_state, &assumptions) {
+            CNFStatus::UNSAT => {
+                for &lit in chunk.iter() {
+                    // Asserting proven literals keeps later calls cheap, and moves
+                    // them to the backbone at the top of the loop
+                    solver_state.add_clause_at_root(vec![lit]);
+                }
+            }
+            CNFStatus::SAT { model } => {
+                let before = candidates.len();
+                filter_candidates(&mut candidates, &model);
+                debug_assert!(candidates.len() < before);
+            }
+        }
+        if let Some(selector) = selector {
+            solver_state.add_clause_at_root(vec![selector.invert()]);
+        }
+    }
+    debug_assert!(!solver_state.is_unsat_at_root());
+    backbone.sort_by_key(|lit| lit.var());
+    Some(Backbone {
+        literals: backbone,
+        solver_calls,
+    })
+}
+
+pub fn print_backbone(formula_file: String, backbone: Option<Backbone>, time: f32) {
+    let mut res_dict: FxHashMap<String, String> = FxHashMap::default();
+    res_dict.insert(
+        "Instance".to_string(),
+        Path::new(&formula_file)
+            .file_name()
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .to_string(),
+    );
+    res_dict.insert("Time".to_string(), time.to_string());
+    match backbone {
+        Some(backbone) => {
+            res_dict.insert("Result".to_string(), "SAT".to_string());
+            res_dict.insert("SolverCalls".to_string(), backbone.solver_calls.to_string());
+            let lits: Vec<String> = backbone
+                .literals
+                .iter()
+                .map(|lit| lit.to_string())
+                .collect();
+            res_dict.insert("Backbone".to_string(), lits.join(" "));
+        }
+        None => {
+            res_dict.insert("Result".to_string(), "UNSAT".to_string());
+        }
+    }
+    let vals = res_dict
+        .iter()
+        .map(|(k, v)| format!("{}:{}", json_quote(k), json_quote(v)))
+        .collect::<Vec<String>>()
+        .join(",");
+    println!("{{{}}}", vals);
+}
+
+pub fn run_backbone(formula_file: String, chunk_size: usize) {
+    let start = std::time::Instant::now();
+    let parsed_out = match parse_cnf(&formula_file) {
+        Ok(p) => p,
+        Err(e) => {
+            println!("c Error: {}", e);
+            exit(1);
+        }
+    };
+    let mut solver_state = SolverState::from_parsed_out(parsed_out);
+    let backbone = match solver_state.preprocess_keep_models() {
+        FormulaPreprocess::TrivialUNSAT => None,
+        FormulaPreprocess::Ok => compute_backbone(&mut solver_state, chunk_size),
+    };
+    let mut total = start.elapsed().as_secs_f32();
+    total = (total * 100.0).round() / 100.0;
+    print_backbone(formula_file, backbone, total);
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,68 @@
+use super::*;
+use crate::differential::{models, Instance};
+use crate::test_util::{clauses, state};
+
+fn make_state(num_vars: usize, raw: &[&[i32]]) -> SolverState {
+    let mut solver_state = state(num_vars, &clauses(raw));
+    assert!(matches!(
+        solver_state.preprocess_keep_models(),
+        FormulaPreprocess::Ok
+    ));
+    solver_state
+}
+
+fn brute_force_backbone(num_vars: usize, clauses: &[&[i32]]) -> Option<Vec<i32>> {
+    let instance = Instance {
+        num_variables: num_vars,
+        clauses: clauses.iter().map(|clause| clause.to_vec()).collect(),
+    };
+    models(&instance).reduce(|always, model| {
+        always.into_iter().filter(|n| model.contains(n)).collect()
+    })
+}
+
+fn check_backbone(num_vars: usize, clauses: &[&[i32]], chunk_size: usize) {
+    let mut solver_state = make_state(num_vars, clauses);
+    let backbone = compute_backbone(&mut solver_state, chunk_size).map(|b| {
+        b.literals
+            .iter()
+            .map(|lit| lit.to_dimacs())
+            .collect()
+    });
+    assert_eq!(backbone, brute_force_backbone(num_vars, clauses));
+}
+
+const FORMULA: &[&[i32]] = &[
+    &[1, 2],
+    &[1, -2],
+    &[-1, 3, 4],
+    &[-3, 5],
+    &[-4, 5],
+    &[6, 7, -5],
+    &[-6, -7],
+    &[8, -8, 2],
+];
+
+#[test]
+fn backbone_single_literal_tests() {
+    check_backbone(8, FORMULA, 1);
+}
+
+#[test]
+fn backbone_chunked_tests() {
+    check_backbone(8, FORMULA, 3);
+    check_backbone(8, FORMULA, 64);
+}
+
+#[test]
+fn backbone_unsat_formula() {
+    let mut solver_state = state(2, &clauses(&[&[1, 2], &[-1, 2], &[1, -2], &[-1, -2]]));
+    solver_state.preprocess_keep_models();
+    assert_eq!(compute_backbone(&mut solver_state, 4), None);
+}
+
+#[test]
+fn backbone_no_units() {
+    // every variable is free in some model
+    check_backbone(4, &[&[1, 2], &[3, 4], &[-1, -3]], 2);
+}
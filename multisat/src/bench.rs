@@ -0,0 +1,300 @@
+use std::fmt::Write;
+use std::io;
+
+use crate::verify::{check_model, json_field, json_quote, read_model, InputFormula, Verdict};
+
+/// How a benchmark run ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Sat,
+    Unsat,
+    Timeout,
+    /// SAT with a model that does not satisfy the input
+    Wrong,
+    /// The solver crashed or printed no result
+    Error,
+}
+
+impl Status {
+    pub const NAMES: [&'static str; 5] = ["SAT", "UNSAT", "TIMEOUT", "WRONG", "ERROR"];
+
+    pub fn name(self) -> &'static str {
+        Status::NAMES[self as usize]
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "SAT" => Some(Status::Sat),
+            "UNSAT" => Some(Status::Unsat),
+            "TIMEOUT" => Some(Status::Timeout),
+            "WRONG" => Some(Status::Wrong),
+            "ERROR" => Some(Status::Error),
+            _ => None,
+        }
+    }
+
+    pub fn solved(self) -> bool {
+        matches!(self, Status::Sat | Status::Unsat)
+    }
+}
+
+/// One row of the results table. Unsolved runs are recorded with the timeout
+/// as their time, so the PAR-2 score only needs the table.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchResult {
+    pub instance: String,
+    pub status: Status,
+    pub time: f64,
+    /// The `c <name>: <value>` lines of the solver output, in order
+    pub stats: Vec<(String, String)>,
+}
+
+/// The `c <name>: <value>` statistics lines of a solver run
+pub fn parse_stats(output: &str) -> Vec<(String, String)> {
+    output
+        .lines()
+        .filter_map(|line| line.strip_prefix("c "))
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+        .collect()
+}
+
+/// The status of a run that finished in time, from the JSON line multisat
+/// prints last. A model is checked against `formula`, UNSAT answers cannot be
+/// checked since the solver writes no proofs.
+pub fn check_output(formula: &InputFormula, output: &str) -> Status {
+    let json = match output
+        .lines()
+        .rev()
+        .find(|line| line.trim_start().starts_with('{'))
+    {
+        Some(json) => json,
+        None => return Status::Error,
+    };
+    match json_field(json, "Result").as_deref() {
+        Some("UNSAT") => Status::Unsat,
+        Some("SAT") => {
+            match read_model(json.as_bytes()).and_then(|model| check_model(formula, &model)) {
+                Ok(Verdict::Satisfied) => Status::Sat,
+                _ => Status::Wrong,
+            }
+        }
+        _ => Status::Error,
+    }
+}
+
+/// Penalized average runtime: solved instances count with their time, all
+/// others with twice the time they were recorded with, i.e. the timeout
+pub fn par2(results: &[BenchResult]) -> f64 {
+    if results.is_empty() {
+        return 0.0;
+    }
+    let total: f64 = results
+        .iter()
+        .map(|result| {
+            if result.status.solved() {
+                result.time
+            } else {
+                2.0 * result.time
+            }
+        })
+        .sum();
+    total / results.len() as f64
+}
+
+pub fn solved_count(results: &[BenchResult]) -> usize {
+    results
+        .iter()
+        .filter(|result| result.status.solved())
+        .count()
+}
+
+/// Every statistic name in the order it first appears
+fn stat_names(results: &[BenchResult]) -> Vec<&str> {
+    let mut names: Vec<&str> = Vec::new();
+    for (name, _) in results.iter().flat_map(|result| result.stats.iter()) {
+        if !names.contains(&name.as_str()) {
+            names.push(name);
+        }
+    }
+    names
+}
+
+fn stat<'a>(result: &'a BenchResult, name: &str) -> &'a str {
+    result
+        .stats
+        .iter()
+        .find(|(stat, _)| stat == name)
+        .map_or("", |(_, value)| value)
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn split_csv_line(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                fields.last_mut().unwrap().push('"');
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(String::new()),
+            c => fields.last_mut().unwrap().push(c),
+        }
+    }
+    fields
+}
+
+/// A header row `instance,status,time` followed by a column per statistic
+pub fn write_csv(results: &[BenchResult]) -> String {
+    let names = stat_names(results);
+    let mut csv = String::from("instance,status,time");
+    for name in names.iter() {
+        write!(csv, ",{}", csv_field(name)).unwrap();
+    }
+    csv.push('\n');
+    for result in results.iter() {
+        write!(
+            csv,
+            "{},{},{}",
+            csv_field(&result.instance),
+            result.status.name(),
+            result.time
+        )
+        .unwrap();
+        for name in names.iter() {
+            write!(csv, ",{}", csv_field(stat(result, name))).unwrap();
+        }
+        csv.push('\n');
+    }
+    csv
+}
+
+/// One JSON object per line, with string values like the line multisat
+/// prints
+pub fn write_json(results: &[BenchResult]) -> String {
+    let mut json = String::new();
+    for result in results.iter() {
+        write!(
+            json,
+            "{{\"Instance\":{},\"Result\":\"{}\",\"Time\":\"{}\"",
+            json_quote(&result.instance),
+            result.status.name(),
+            result.time
+        )
+        .unwrap();
+        for (name, value) in result.stats.iter() {
+            write!(json, ",{}:{}", json_quote(name), json_quote(value)).unwrap();
+        }
+        json.push_str("}\n");
+    }
+    json
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn parse_row(
+    instance: Option<&str>,
+    status: Option<&str>,
+    time: Option<&str>,
+) -> io::Result<BenchResult> {
+    let instance = instance.ok_or_else(|| invalid_data("Row without instance".to_string()))?;
+    let status = status
+        .and_then(Status::from_name)
+        .ok_or_else(|| invalid_data(format!("Invalid status for {}", instance)))?;
+    let time = time
+        .and_then(|time| time.parse().ok())
+        .ok_or_else(|| invalid_data(format!("Invalid time for {}", instance)))?;
+    Ok(BenchResult {
+        instance: instance.to_string(),
+        status,
+        time,
+        stats: Vec::new(),
+    })
+}
+
+/// Reads a table written by `write_csv` or `write_json`, without the
+/// statistics
+pub fn read_results(text: &str) -> io::Result<Vec<BenchResult>> {
+    let mut lines = text.lines().filter(|line| !line.trim().is_empty());
+    match lines.next() {
+        None => Ok(Vec::new()),
+        Some(first) if first.trim_start().starts_with('{') => std::iter::once(first)
+            .chain(lines)
+            .map(|json| {
+                parse_row(
+                    json_field(json, "Instance").as_deref(),
+                    json_field(json, "Result").as_deref(),
+                    json_field(json, "Time").as_deref(),
+                )
+            })
+            .collect(),
+        Some(header) => {
+            if !header.starts_with("instance,status,time") {
+                return Err(invalid_data(format!("Invalid CSV header {}", header)));
+            }
+            lines
+                .map(|line| {
+                    let fields = split_csv_line(line);
+                    let field = |idx: usize| fields.get(idx).map(String::as_str);
+                    parse_row(field(0), field(1), field(2))
+                })
+                .collect()
+        }
+    }
+}
+
+/// Slowdowns below this many seconds are noise
+pub const MIN_SLOWDOWN_SECONDS: f64 = 0.5;
+
+/// Every instance the candidate does worse on than the baseline, with a
+/// description: no longer solved, a different answer, a wrong model, missing,
+/// or more than `slowdown` times slower
+pub fn regressions(
+    baseline: &[BenchResult],
+    candidate: &[BenchResult],
+    slowdown: f64,
+) -> Vec<(String, String)> {
+    let mut found: Vec<(String, String)> = Vec::new();
+    for base in baseline.iter() {
+        let mut flag = |message: String| found.push((base.instance.clone(), message));
+        let new = match candidate.iter().find(|new| new.instance == base.instance) {
+            Some(new) => new,
+            None => {
+                flag("missing from the candidate".to_string());
+                continue;
+            }
+        };
+        if new.status == Status::Wrong && base.status != Status::Wrong {
+            flag("wrong model".to_string());
+        } else if base.status.solved() && !new.status.solved() {
+            flag(format!("{} now {}", base.status.name(), new.status.name()));
+        } else if base.status.solved() && new.status != base.status {
+            flag(format!(
+                "answer changed from {} to {}",
+                base.status.name(),
+                new.status.name()
+            ));
+        } else if base.status.solved()
+            && new.time > base.time * slowdown
+            && new.time - base.time > MIN_SLOWDOWN_SECONDS
+        {
+            flag(format!("{:.2}s now {:.2}s", base.time, new.time));
+        }
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests;
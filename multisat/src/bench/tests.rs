@@ -0,0 +1,116 @@
+use super::*;
+use crate::verify::read_formula;
+
+const FORMULA: &str = "p cnf 3 2\n1 -2 0\n2 3 0\n";
+
+fn result(instance: &str, status: Status, time: f64) -> BenchResult {
+    BenchResult {
+        instance: instance.to_string(),
+        status,
+        time,
+        stats: Vec::new(),
+    }
+}
+
+#[test]
+fn outputs_are_verified() {
+    let formula = read_formula(FORMULA.as_bytes()).unwrap();
+    let sat = "c restarts: 0 blocked 0 reused trail 0\nChecking result\n{\"Solution\":\"1 true  2 true  3 false \",\"Instance\":\"f.cnf\",\"Result\":\"SAT\",\"Time\":\"0\"}\n";
+    assert_eq!(check_output(&formula, sat), Status::Sat);
+    let wrong = "{\"Solution\":\"1 false  2 true  3 false \",\"Result\":\"SAT\"}\n";
+    assert_eq!(check_output(&formula, wrong), Status::Wrong);
+    let unsat = "{\"Instance\":\"f.cnf\",\"Result\":\"UNSAT\",\"Time\":\"0\"}\n";
+    assert_eq!(check_output(&formula, unsat), Status::Unsat);
+    assert_eq!(
+        check_output(&formula, "c Error: no such file\n"),
+        Status::Error
+    );
+    assert_eq!(
+        parse_stats(sat),
+        vec![(
+            "restarts".to_string(),
+            "0 blocked 0 reused trail 0".to_string()
+        )]
+    );
+}
+
+#[test]
+fn tables_round_trip() {
+    let mut results = vec![
+        result("a,b.cnf", Status::Sat, 1.5),
+        result("d\\\"e\",\"Result\":\"SAT.cnf", Status::Unsat, 2.0),
+        result("c.cnf", Status::Timeout, 10.0),
+    ];
+    results[0].stats = vec![("rephases".to_string(), "3".to_string())];
+    results[1].stats = vec![("mode \"switches\"".to_string(), "1 \\ 2".to_string())];
+    results[2].stats = vec![("mode switches".to_string(), "1".to_string())];
+    let csv = write_csv(&results);
+    assert_eq!(
+        csv.lines().next(),
+        Some("instance,status,time,rephases,\"mode \"\"switches\"\"\",mode switches")
+    );
+    assert_eq!(csv.lines().nth(1), Some("\"a,b.cnf\",SAT,1.5,3,,"));
+    let json = write_json(&results);
+    let quoted = json.lines().nth(1).unwrap();
+    assert_eq!(
+        json_field(quoted, "mode \"switches\"").as_deref(),
+        Some("1 \\ 2")
+    );
+    assert_eq!(json_field(quoted, "Result").as_deref(), Some("UNSAT"));
+    for text in [csv, json] {
+        let read = read_results(&text).unwrap();
+        assert_eq!(read.len(), 3);
+        for (read, written) in read.iter().zip(results.iter()) {
+            assert_eq!(
+                (&read.instance, read.status, read.time),
+                (&written.instance, written.status, written.time)
+            );
+        }
+    }
+    assert!(read_results("name,time\nx,1\n").is_err());
+    assert!(read_results("instance,status,time\nx,DONE,1\n").is_err());
+}
+
+#[test]
+fn par2_penalizes_unsolved_instances() {
+    let results = vec![
+        result("a", Status::Sat, 1.0),
+        result("b", Status::Unsat, 3.0),
+        result("c", Status::Timeout, 10.0),
+        result("d", Status::Wrong, 10.0),
+    ];
+    assert_eq!(solved_count(&results), 2);
+    assert_eq!(par2(&results), (1.0 + 3.0 + 20.0 + 20.0) / 4.0);
+    assert_eq!(par2(&[]), 0.0);
+}
+
+#[test]
+fn regressions_are_flagged() {
+    let baseline = vec![
+        result("same", Status::Sat, 1.0),
+        result("slower", Status::Unsat, 1.0),
+        result("noise", Status::Sat, 0.1),
+        result("lost", Status::Sat, 2.0),
+        result("flipped", Status::Sat, 2.0),
+        result("gone", Status::Unsat, 2.0),
+        result("still hard", Status::Timeout, 10.0),
+    ];
+    let candidate = vec![
+        result("same", Status::Sat, 1.2),
+        result("slower", Status::Unsat, 3.0),
+        result("noise", Status::Sat, 0.4),
+        result("lost", Status::Timeout, 10.0),
+        result("flipped", Status::Unsat, 2.0),
+        result("still hard", Status::Wrong, 10.0),
+    ];
+    let found = regressions(&baseline, &candidate, 1.5);
+    let flagged: Vec<&str> = found
+        .iter()
+        .map(|(instance, _)| instance.as_str())
+        .collect();
+    assert_eq!(
+        flagged,
+        vec!["slower", "lost", "flipped", "gone", "still hard"]
+    );
+    assert!(regressions(&baseline, &baseline, 1.5).is_empty());
+}
@@ -0,0 +1,205 @@
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::process::{exit, Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use multisat::bench::*;
+use multisat::verify::read_formula;
+
+const DEFAULT_TIMEOUT: f64 = 60.0;
+const DEFAULT_SLOWDOWN: f64 = 1.5;
+
+fn usage() -> ! {
+    println!("usage: bench run <dir|formula.cnf>... [--timeout <seconds>] [--out <results.csv|results.json>] [--solver <multisat>] [-- <solver options>]");
+    println!("       bench compare <baseline> <candidate> [--slowdown <factor>]");
+    exit(1);
+}
+
+fn fail(e: io::Error) -> ! {
+    println!("c Error: {}", e);
+    exit(1);
+}
+
+/// The files given, with directories replaced by the `.cnf` files in them
+fn collect_instances(paths: &[String]) -> io::Result<Vec<String>> {
+    let mut instances: Vec<String> = Vec::new();
+    for path in paths.iter() {
+        if !Path::new(path).is_dir() {
+            instances.push(path.clone());
+            continue;
+        }
+        let mut files: Vec<PathBuf> = fs::read_dir(path)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<io::Result<_>>()?;
+        files.retain(|file| file.extension().is_some_and(|ext| ext == "cnf"));
+        files.sort();
+        instances.extend(files.iter().map(|file| file.display().to_string()));
+    }
+    Ok(instances)
+}
+
+/// Runs the solver on `instance` in its own process, killed after `timeout`
+/// seconds. The output is verified against the formula as read from disk.
+fn run_instance(solver: &Path, options: &[String], instance: &str, timeout: f64) -> BenchResult {
+    let mut result = BenchResult {
+        instance: instance.to_string(),
+        status: Status::Error,
+        time: timeout,
+        stats: Vec::new(),
+    };
+    let formula = match File::open(instance).and_then(|file| read_formula(BufReader::new(file))) {
+        Ok(formula) => formula,
+        Err(e) => {
+            println!("c Error: {}: {}", instance, e);
+            return result;
+        }
+    };
+    let start = Instant::now();
+    let mut child = match Command::new(solver)
+        .arg(instance)
+        .args(options)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(e) => fail(e),
+    };
+    // read while the solver runs, so a full pipe cannot block it
+    let mut stdout = child.stdout.take().unwrap();
+    let reader = thread::spawn(move || {
+        let mut output = String::new();
+        stdout.read_to_string(&mut output).map(|_| output)
+    });
+    let exited = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break Some(status),
+            Ok(None) if start.elapsed().as_secs_f64() < timeout => {
+                thread::sleep(Duration::from_millis(10))
+            }
+            _ => {
+                let _ = child.kill();
+                let _ = child.wait();
+                break None;
+            }
+        }
+    };
+    let time = start.elapsed().as_secs_f64();
+    let output = reader.join().unwrap().unwrap_or_default();
+    result.stats = parse_stats(&output);
+    result.status = match exited {
+        None => Status::Timeout,
+        Some(status) if !status.success() => Status::Error,
+        Some(_) => check_output(&formula, &output),
+    };
+    if result.status.solved() {
+        result.time = (time * 1000.0).round() / 1000.0;
+    }
+    result
+}
+
+fn run(args: &[String]) {
+    let (args, options) = match args.iter().position(|arg| arg == "--") {
+        Some(idx) => (&args[..idx], &args[idx + 1..]),
+        None => (args, &args[args.len()..]),
+    };
+    let mut timeout = DEFAULT_TIMEOUT;
+    let mut out = "bench.csv".to_string();
+    let mut solver = env::current_exe()
+        .map(|exe| exe.with_file_name(format!("multisat{}", env::consts::EXE_SUFFIX)))
+        .unwrap_or_else(|e| fail(e));
+    let mut paths: Vec<String> = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--timeout" => {
+                timeout = iter
+                    .next()
+                    .and_then(|t| t.parse().ok())
+                    .filter(|&t: &f64| t > 0.0)
+                    .unwrap_or_else(|| usage())
+            }
+            "--out" => out = iter.next().cloned().unwrap_or_else(|| usage()),
+            "--solver" => solver = iter.next().map(PathBuf::from).unwrap_or_else(|| usage()),
+            _ => paths.push(arg.clone()),
+        }
+    }
+    let instances = collect_instances(&paths).unwrap_or_else(|e| fail(e));
+    if instances.is_empty() {
+        usage();
+    }
+
+    let mut results: Vec<BenchResult> = Vec::new();
+    for instance in instances.iter() {
+        let result = run_instance(&solver, options, instance, timeout);
+        println!("c {} {} {}", instance, result.status.name(), result.time);
+        results.push(result);
+    }
+    let table = if out.ends_with(".json") {
+        write_json(&results)
+    } else {
+        write_csv(&results)
+    };
+    fs::write(&out, table).unwrap_or_else(|e| fail(e));
+    println!(
+        "c solved {} of {} instances, PAR-2 {:.3}",
+        solved_count(&results),
+        results.len(),
+        par2(&results)
+    );
+    if results.iter().any(|result| result.status == Status::Wrong) {
+        println!("c wrong models were reported, see {}", out);
+        exit(1);
+    }
+}
+
+fn compare(args: &[String]) {
+    let slowdown = match args.len() {
+        2 => DEFAULT_SLOWDOWN,
+        4 if args[2] == "--slowdown" => args[3]
+            .parse()
+            .ok()
+            .filter(|&factor: &f64| factor >= 1.0)
+            .unwrap_or_else(|| usage()),
+        _ => usage(),
+    };
+    let read = |file: &String| {
+        fs::read_to_string(file)
+            .and_then(|text| read_results(&text))
+            .unwrap_or_else(|e| fail(e))
+    };
+    let baseline = read(&args[0]);
+    let candidate = read(&args[1]);
+    for (name, results) in [("baseline", &baseline), ("candidate", &candidate)] {
+        println!(
+            "c {}: solved {} of {}, PAR-2 {:.3}",
+            name,
+            solved_count(results),
+            results.len(),
+            par2(results)
+        );
+    }
+    let found = regressions(&baseline, &candidate, slowdown);
+    for (instance, message) in found.iter() {
+        println!("c regression {}: {}", instance, message);
+    }
+    if !found.is_empty() {
+        exit(1);
+    }
+    println!("c no regressions");
+}
+
+/// Runs multisat on a set of instances with a timeout, one process each, and
+/// writes a table of the verified results, or compares two such tables and
+/// exits with 1 if the candidate regressed.
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    match args.get(1).map(String::as_str) {
+        Some("run") => run(&args[2..]),
+        Some("compare") => compare(&args[2..]),
+        _ => usage(),
+    }
+}
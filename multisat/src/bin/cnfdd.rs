@@ -0,0 +1,94 @@
+use std::env;
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::panic;
+use std::process::exit;
+
+use multisat::delta::{minimize, record_panic_location, Tester};
+use multisat::differential::{Instance, MAX_BRUTE_FORCE_VARIABLES};
+use multisat::ds::SolverOptions;
+use multisat::verify::read_formula;
+
+fn usage() -> ! {
+    println!("usage: cnfdd <input.cnf> <output.cnf> [--keep-models] [solver options]");
+    println!("       shrinks a formula on which the solver panics or answers wrongly");
+    exit(1);
+}
+
+fn read_instance(file: &str) -> std::io::Result<Instance> {
+    let formula = read_formula(BufReader::new(File::open(file)?))?;
+    Ok(Instance {
+        num_variables: formula.num_variables,
+        clauses: formula
+            .clauses
+            .into_iter()
+            .map(|clause| clause.lits)
+            .collect(),
+    })
+}
+
+/// Delta debugging of solver failures. Runs the solver in this process on the
+/// input, then keeps removing clauses and literals and renaming variables as
+/// long as it fails the same way, and writes the smallest failing formula.
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.len() < 3 {
+        usage();
+    }
+    let keep_models = args[3..].iter().any(|arg| arg == "--keep-models");
+    let solver_args: Vec<String> = args[3..]
+        .iter()
+        .filter(|arg| *arg != "--keep-models")
+        .cloned()
+        .collect();
+    let tester = Tester {
+        options: SolverOptions::from_args(&solver_args).unwrap_or_else(|| usage()),
+        eliminate: !keep_models,
+    };
+    let instance = read_instance(&args[1]).unwrap_or_else(|e| {
+        println!("c Error: {}", e);
+        exit(1);
+    });
+
+    // the solver is expected to panic over and over, keep the output readable
+    // and remember where it panicked
+    panic::set_hook(Box::new(record_panic_location));
+    let failure = match tester.failure(&instance) {
+        Some(failure) => failure,
+        None => {
+            println!(
+                "c the solver handles {} correctly, nothing to minimize",
+                args[1]
+            );
+            if instance.num_variables > MAX_BRUTE_FORCE_VARIABLES {
+                println!(
+                    "c with more than {} variables an UNSAT answer only counts as wrong if the solver finds a model with {:?}",
+                    MAX_BRUTE_FORCE_VARIABLES,
+                    tester.reference()
+                );
+            }
+            exit(1);
+        }
+    };
+    println!("c failure: {:?}", failure);
+
+    let mut calls = 0;
+    let minimal = minimize(&instance, |candidate| {
+        calls += 1;
+        tester
+            .failure(candidate)
+            .is_some_and(|other| other.matches(&failure))
+    });
+    if let Err(e) = fs::write(&args[2], minimal.to_dimacs()) {
+        println!("c Error: {}", e);
+        exit(1);
+    }
+    println!(
+        "c reduced {} variables {} clauses to {} variables {} clauses in {} solver calls",
+        instance.num_variables,
+        instance.clauses.len(),
+        minimal.num_variables,
+        minimal.clauses.len(),
+        calls
+    );
+}
@@ -0,0 +1,33 @@
+use std::env;
+use std::process::exit;
+
+use multisat::verify::{verify_files, Verdict};
+
+/// Checks a model against the original formula, independently of the solver.
+/// Exits with 0 if every clause is satisfied, 1 if one is falsified and 2 if
+/// the input cannot be read.
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.len() != 3 {
+        println!("usage: verify <formula.cnf> <model>");
+        println!("       the model is given as competition v lines or multisat JSON output");
+        exit(2);
+    }
+    match verify_files(&args[1], &args[2]) {
+        Ok(Verdict::Satisfied) => println!("s VERIFIED"),
+        Ok(Verdict::Falsified(clause)) => {
+            let lits: Vec<String> = clause.lits.iter().map(|lit| lit.to_string()).collect();
+            println!(
+                "c clause on line {} is falsified: {} 0",
+                clause.line,
+                lits.join(" ")
+            );
+            println!("s FALSIFIED");
+            exit(1);
+        }
+        Err(e) => {
+            println!("c Error: {}", e);
+            exit(2);
+        }
+    }
+}
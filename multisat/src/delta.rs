@@ -0,0 +1,217 @@
+use std::cell::RefCell;
+use std::panic::PanicHookInfo;
+
+use crate::differential::{brute_force, solve_caught, Instance, MAX_BRUTE_FORCE_VARIABLES};
+use crate::ds::heuristic::HeuristicKind;
+use crate::ds::restart::RestartPolicy;
+use crate::ds::*;
+use crate::verify::{check_model, Verdict};
+use crate::CNFStatus;
+
+thread_local! {
+    static PANIC_LOCATION: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// A panic hook that remembers where the solver panicked instead of printing
+/// it, so `Tester::failure` can report the location
+pub fn record_panic_location(info: &PanicHookInfo) {
+    let location = info
+        .location()
+        .map(|location| format!("{}:{}", location.file(), location.line()));
+    PANIC_LOCATION.with(|recorded| *recorded.borrow_mut() = location);
+}
+
+/// How a run of the solver went wrong
+#[derive(Debug, Clone, PartialEq)]
+pub enum Failure {
+    /// The solver panicked, at `file:line` if `record_panic_location` is the
+    /// panic hook
+    Panic {
+        location: Option<String>,
+        message: String,
+    },
+    /// The model does not satisfy the formula
+    InvalidModel,
+    /// UNSAT although the formula has a model
+    WrongUnsat,
+}
+
+impl Failure {
+    /// Whether `other` is the same failure. Panics are the same if they happen
+    /// at the same place, their messages often contain values that change as
+    /// the formula shrinks. Without locations the messages have to agree.
+    pub fn matches(&self, other: &Failure) -> bool {
+        match (self, other) {
+            (
+                Failure::Panic {
+                    location: Some(location),
+                    ..
+                },
+                Failure::Panic {
+                    location: Some(other),
+                    ..
+                },
+            ) => location == other,
+            _ => self == other,
+        }
+    }
+}
+
+/// The solver configuration whose failures are minimized
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Tester {
+    pub options: SolverOptions,
+    /// Whether preprocessing eliminates variables and clauses
+    pub eliminate: bool,
+}
+
+fn is_model(instance: &Instance, model: &[i32]) -> bool {
+    model.len() == instance.num_variables
+        && matches!(
+            check_model(&instance.input_formula(), model),
+            Ok(Verdict::Satisfied)
+        )
+}
+
+impl Tester {
+    /// Runs the solver on `instance` in this process, None if it gets it right
+    pub fn failure(&self, instance: &Instance) -> Option<Failure> {
+        PANIC_LOCATION.with(|recorded| recorded.borrow_mut().take());
+        match solve_caught(instance, &self.options, self.eliminate) {
+            Err(message) => Some(Failure::Panic {
+                location: PANIC_LOCATION.with(|recorded| recorded.borrow_mut().take()),
+                message,
+            }),
+            Ok(CNFStatus::SAT { model }) => {
+                (!is_model(instance, &model)).then_some(Failure::InvalidModel)
+            }
+            Ok(CNFStatus::UNSAT) => self
+                .known_satisfiable(instance)
+                .then_some(Failure::WrongUnsat),
+        }
+    }
+
+    /// Settings that differ from the tested ones in everything, so a bug in
+    /// one configuration is unlikely to show up in the other as well
+    pub fn reference(&self) -> Tester {
+        let options = &self.options;
+        Tester {
+            options: SolverOptions {
+                chrono_threshold: match options.chrono_threshold {
+                    None => Some(0),
+                    Some(_) => None,
+                },
+                heuristic: match options.heuristic {
+                    HeuristicKind::VMTF => HeuristicKind::VSIDS,
+                    _ => HeuristicKind::VMTF,
+                },
+                restart: match options.restart {
+                    RestartPolicy::Luby => RestartPolicy::Glucose,
+                    _ => RestartPolicy::Luby,
+                },
+                reuse_trail: !options.reuse_trail,
+            },
+            eliminate: !self.eliminate,
+        }
+    }
+
+    /// Whether `instance` has a model, decided by the truth table if it is
+    /// small enough. Otherwise a run with the `reference` settings looks for
+    /// one, and only a model that checks out counts. A wrong UNSAT that both
+    /// configurations give goes unnoticed there.
+    fn known_satisfiable(&self, instance: &Instance) -> bool {
+        if instance.num_variables <= MAX_BRUTE_FORCE_VARIABLES {
+            return brute_force(instance).is_some();
+        }
+        let reference = self.reference();
+        match solve_caught(instance, &reference.options, reference.eliminate) {
+            Ok(CNFStatus::SAT { model }) => is_model(instance, &model),
+            _ => false,
+        }
+    }
+}
+
+/// Tries to drop chunks of clauses, starting with halves of the formula and
+/// going down to single clauses
+fn remove_clauses(current: &mut Instance, fails: &mut impl FnMut(&Instance) -> bool) -> bool {
+    let mut changed = false;
+    let mut chunk = current.clauses.len().div_ceil(2).max(1);
+    loop {
+        let mut idx = 0;
+        while idx < current.clauses.len() {
+            let mut candidate = current.clone();
+            let end = (idx + chunk).min(candidate.clauses.len());
+            candidate.clauses.drain(idx..end);
+            if fails(&candidate) {
+                *current = candidate;
+                changed = true;
+            } else {
+                idx += chunk;
+            }
+        }
+        if chunk == 1 {
+            return changed;
+        }
+        chunk = chunk.div_ceil(2);
+    }
+}
+
+/// Tries to drop every literal of every clause, one at a time
+fn remove_literals(current: &mut Instance, fails: &mut impl FnMut(&Instance) -> bool) -> bool {
+    let mut changed = false;
+    for clause_idx in 0..current.clauses.len() {
+        let mut lit_idx = 0;
+        while lit_idx < current.clauses[clause_idx].len() {
+            let mut candidate = current.clone();
+            candidate.clauses[clause_idx].remove(lit_idx);
+            if fails(&candidate) {
+                *current = candidate;
+                changed = true;
+            } else {
+                lit_idx += 1;
+            }
+        }
+    }
+    changed
+}
+
+/// Tries to number the variables that still occur from 1 on, in the order of
+/// their first occurrence
+fn rename_variables(current: &mut Instance, fails: &mut impl FnMut(&Instance) -> bool) -> bool {
+    let mut renamed: Vec<i32> = vec![0; current.num_variables + 1];
+    let mut num_variables = 0;
+    let mut candidate = current.clone();
+    for lit in candidate.clauses.iter_mut().flatten() {
+        let var = lit.unsigned_abs() as usize;
+        if renamed[var] == 0 {
+            num_variables += 1;
+            renamed[var] = num_variables;
+        }
+        *lit = lit.signum() * renamed[var];
+    }
+    candidate.num_variables = num_variables as usize;
+    if candidate == *current || !fails(&candidate) {
+        return false;
+    }
+    *current = candidate;
+    true
+}
+
+/// Shrinks `instance` while `fails` holds for it, which it has to do at the
+/// start: removes clauses, then literals, then renames the variables that are
+/// left, and repeats until none of them succeeds. The result is minimal in
+/// the sense that dropping any single clause or literal makes `fails` false.
+pub fn minimize(instance: &Instance, mut fails: impl FnMut(&Instance) -> bool) -> Instance {
+    let mut current = instance.clone();
+    loop {
+        let mut changed = remove_clauses(&mut current, &mut fails);
+        changed |= remove_literals(&mut current, &mut fails);
+        changed |= rename_variables(&mut current, &mut fails);
+        if !changed {
+            return current;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
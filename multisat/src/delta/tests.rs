@@ -0,0 +1,103 @@
+use super::*;
+use crate::differential::random_options;
+
+#[test]
+fn clauses_and_literals_are_removed_and_variables_renamed() {
+    let instance = Instance {
+        num_variables: 6,
+        clauses: vec![vec![1, 2], vec![-3, 5, 6], vec![4, -5], vec![2, -6]],
+    };
+    let mut calls = 0;
+    let minimal = minimize(&instance, |candidate| {
+        calls += 1;
+        candidate.clauses.iter().flatten().any(|&lit| lit < 0)
+    });
+    // only the last clause survives, with its negative literal renamed
+    assert_eq!(
+        minimal,
+        Instance {
+            num_variables: 1,
+            clauses: vec![vec![-1]],
+        }
+    );
+    assert!(calls > 0);
+}
+
+#[test]
+fn unsat_formulas_shrink_to_a_minimal_core() {
+    // pigeonhole with an unrelated satisfiable part over fresh variables
+    let mut instance = Instance::pigeonhole(2);
+    instance.num_variables += 3;
+    instance.clauses.insert(1, vec![7, 8, -9]);
+    instance.clauses.push(vec![-7, 9]);
+    let unsat = |candidate: &Instance| brute_force(candidate).is_none();
+    let minimal = minimize(&instance, unsat);
+    assert!(unsat(&minimal));
+    assert!(minimal.num_variables <= 6);
+    assert!(minimal.clauses.len() <= instance.clauses.len() - 2);
+    for idx in 0..minimal.clauses.len() {
+        let mut fewer = minimal.clone();
+        fewer.clauses.remove(idx);
+        assert!(!unsat(&fewer), "clause {} is not needed", idx);
+        for lit_idx in 0..minimal.clauses[idx].len() {
+            let mut shorter = minimal.clone();
+            shorter.clauses[idx].remove(lit_idx);
+            assert!(!unsat(&shorter), "literal {} of clause {}", lit_idx, idx);
+        }
+    }
+}
+
+#[test]
+fn correct_answers_are_no_failure() {
+    let mut rng = Rng::new(0xdd);
+    for round in 0..100 {
+        let num_variables = 3 + rng.below(MAX_BRUTE_FORCE_VARIABLES - 2);
+        let instance = Instance::random_ksat(&mut rng, num_variables, 3);
+        let tester = Tester {
+            options: random_options(&mut rng),
+            eliminate: round % 2 == 0,
+        };
+        assert_eq!(tester.failure(&instance), None, "{}", instance.to_dimacs());
+    }
+    // too large for the truth table, the UNSAT answer is not contradicted
+    let tester = Tester::default();
+    assert_eq!(tester.failure(&Instance::pigeonhole(4)), None);
+}
+
+#[test]
+fn panics_match_by_location() {
+    let panic = |location: Option<&str>, message: &str| Failure::Panic {
+        location: location.map(str::to_string),
+        message: message.to_string(),
+    };
+    let original = panic(Some("src/ds.rs:10"), "index out of bounds: the len is 9");
+    assert!(original.matches(&panic(Some("src/ds.rs:10"), "the len is 3")));
+    assert!(!original.matches(&panic(
+        Some("src/ds.rs:11"),
+        "index out of bounds: the len is 9"
+    )));
+    assert!(!original.matches(&Failure::InvalidModel));
+    // without the hook only the same message counts
+    assert!(panic(None, "boom").matches(&panic(None, "boom")));
+    assert!(!panic(None, "boom").matches(&panic(None, "bang")));
+}
+
+#[test]
+fn reference_differs_in_every_setting() {
+    let mut rng = Rng::new(0x7e);
+    for round in 0..20 {
+        let tester = Tester {
+            options: random_options(&mut rng),
+            eliminate: round % 2 == 0,
+        };
+        let reference = tester.reference();
+        assert_ne!(reference.eliminate, tester.eliminate);
+        assert_ne!(
+            reference.options.chrono_threshold.is_some(),
+            tester.options.chrono_threshold.is_some()
+        );
+        assert_ne!(reference.options.heuristic, tester.options.heuristic);
+        assert_ne!(reference.options.restart, tester.options.restart);
+        assert_ne!(reference.options.reuse_trail, tester.options.reuse_trail);
+    }
+}
@@ -0,0 +1,319 @@
+use std::fmt::Write;
+use std::panic::{self, AssertUnwindSafe};
+
+use crate::ds::heuristic::HeuristicKind;
+use crate::ds::restart::RestartPolicy;
+use crate::ds::*;
+use crate::verify::{check_model, InputClause, InputFormula, Verdict};
+use crate::{solve_with_assumptions, CNFStatus};
+
+/// Formulas up to this many variables are decided by the truth table
+pub const MAX_BRUTE_FORCE_VARIABLES: usize = 14;
+
+/// A small formula for differential testing, in DIMACS literals
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Instance {
+    pub num_variables: usize,
+    pub clauses: Vec<Vec<i32>>,
+}
+
+impl Instance {
+    /// Random k-SAT with distinct variables per clause, at about the clause to
+    /// variable ratio where half of the formulas are satisfiable
+    pub fn random_ksat(rng: &mut Rng, num_variables: usize, k: usize) -> Self {
+        assert!(k >= 1 && k <= num_variables);
+        let ratio_permille = match k {
+            1 => 500,
+            2 => 1000,
+            3 => 4260,
+            4 => 9930,
+            _ => 21120,
+        };
+        // some spread around the threshold, 80% to 120%
+        let ratio_permille = ratio_permille * (800 + rng.below(401)) / 1000;
+        let num_clauses = (num_variables * ratio_permille / 1000).max(1);
+        let clauses = (0..num_clauses)
+            .map(|_| {
+                let mut clause: Vec<i32> = Vec::with_capacity(k);
+                while clause.len() < k {
+                    let var = rng.below(num_variables) as i32 + 1;
+                    if clause.iter().all(|lit| lit.abs() != var) {
+                        clause.push(if rng.coin() { var } else { -var });
+                    }
+                }
+                clause
+            })
+            .collect();
+        Instance {
+            num_variables,
+            clauses,
+        }
+    }
+
+    /// `holes + 1` pigeons in `holes` holes, always UNSAT. Pigeon p sits in hole
+    /// h if variable `p * holes + h + 1` is true.
+    pub fn pigeonhole(holes: usize) -> Self {
+        let var = |pigeon: usize, hole: usize| (pigeon * holes + hole + 1) as i32;
+        let mut clauses: Vec<Vec<i32>> = (0..=holes)
+            .map(|pigeon| (0..holes).map(|hole| var(pigeon, hole)).collect())
+            .collect();
+        for hole in 0..holes {
+            for first in 0..=holes {
+                for second in first + 1..=holes {
+                    clauses.push(vec![-var(first, hole), -var(second, hole)]);
+                }
+            }
+        }
+        Instance {
+            num_variables: (holes + 1) * holes,
+            clauses,
+        }
+    }
+
+    /// Random parity constraints over `width` variables each, encoded by
+    /// forbidding every assignment of the wrong parity
+    pub fn random_xor(
+        rng: &mut Rng,
+        num_variables: usize,
+        num_constraints: usize,
+        width: usize,
+    ) -> Self {
+        assert!(width >= 1 && width <= num_variables);
+        let mut clauses: Vec<Vec<i32>> = Vec::new();
+        for _ in 0..num_constraints {
+            let mut vars: Vec<i32> = Vec::with_capacity(width);
+            while vars.len() < width {
+                let var = rng.below(num_variables) as i32 + 1;
+                if !vars.contains(&var) {
+                    vars.push(var);
+                }
+            }
+            let parity = rng.coin();
+            for bits in 0..1u32 << width {
+                // the clause is false exactly on the assignment given by bits
+                if (bits.count_ones() % 2 == 1) == parity {
+                    continue;
+                }
+                clauses.push(
+                    vars.iter()
+                        .enumerate()
+                        .map(|(idx, &var)| if bits & (1 << idx) != 0 { -var } else { var })
+                        .collect(),
+                );
+            }
+        }
+        Instance {
+            num_variables,
+            clauses,
+        }
+    }
+
+    /// Coloring of a random graph, each edge present with the given chance in
+    /// permille. Vertex v has color c if variable `v * colors + c + 1` is true.
+    pub fn coloring(rng: &mut Rng, vertices: usize, colors: usize, edge_permille: usize) -> Self {
+        let var = |vertex: usize, color: usize| (vertex * colors + color + 1) as i32;
+        let mut clauses: Vec<Vec<i32>> = Vec::new();
+        for vertex in 0..vertices {
+            clauses.push((0..colors).map(|color| var(vertex, color)).collect());
+            for first in 0..colors {
+                for second in first + 1..colors {
+                    clauses.push(vec![-var(vertex, first), -var(vertex, second)]);
+                }
+            }
+        }
+        for first in 0..vertices {
+            for second in first + 1..vertices {
+                if rng.below(1000) >= edge_permille {
+                    continue;
+                }
+                for color in 0..colors {
+                    clauses.push(vec![-var(first, color), -var(second, color)]);
+                }
+            }
+        }
+        Instance {
+            num_variables: vertices * colors,
+            clauses,
+        }
+    }
+
+    /// Decodes arbitrary bytes, e.g. from a fuzzer, into a formula over at most
+    /// `MAX_BRUTE_FORCE_VARIABLES` variables. The first byte gives the number
+    /// of variables, every following byte is a literal and 0 ends a clause.
+    /// Duplicate literals, tautologies and empty clauses are kept on purpose.
+    pub fn from_bytes(data: &[u8]) -> Self {
+        let num_variables = match data.first() {
+            Some(&byte) => byte as usize % MAX_BRUTE_FORCE_VARIABLES + 1,
+            None => return Instance::default(),
+        };
+        let mut clauses: Vec<Vec<i32>> = Vec::new();
+        let mut clause: Vec<i32> = Vec::new();
+        for &byte in data[1..].iter() {
+            if byte == 0 {
+                clauses.push(std::mem::take(&mut clause));
+                continue;
+            }
+            let code = byte as usize % (2 * num_variables);
+            let var = (code / 2 + 1) as i32;
+            clause.push(if code & 1 == 0 { var } else { -var });
+        }
+        if !clause.is_empty() {
+            clauses.push(clause);
+        }
+        Instance {
+            num_variables,
+            clauses,
+        }
+    }
+
+    pub fn to_dimacs(&self) -> String {
+        let mut dimacs = format!("p cnf {} {}\n", self.num_variables, self.clauses.len());
+        for clause in self.clauses.iter() {
+            for lit in clause.iter() {
+                write!(dimacs, "{} ", lit).unwrap();
+            }
+            dimacs.push_str("0\n");
+        }
+        dimacs
+    }
+
+    /// The formula as the verifier sees it, with the lines of `to_dimacs`
+    pub fn input_formula(&self) -> InputFormula {
+        InputFormula {
+            num_variables: self.num_variables,
+            clauses: self
+                .clauses
+                .iter()
+                .enumerate()
+                .map(|(idx, clause)| InputClause {
+                    line: idx + 2,
+                    lits: clause.clone(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Every model of `instance`, by trying all assignments in order. A model has
+/// one DIMACS literal per variable.
+pub fn models(instance: &Instance) -> impl Iterator<Item = Vec<i32>> + '_ {
+    assert!(instance.num_variables <= MAX_BRUTE_FORCE_VARIABLES);
+    (0..1u32 << instance.num_variables)
+        .map(|bits| {
+            (1..=instance.num_variables as i32)
+                .map(|var| {
+                    if bits & (1 << (var - 1)) != 0 {
+                        var
+                    } else {
+                        -var
+                    }
+                })
+                .collect::<Vec<i32>>()
+        })
+        .filter(|model| {
+            instance.clauses.iter().all(|clause| {
+                clause
+                    .iter()
+                    .any(|&lit| model[lit.unsigned_abs() as usize - 1] == lit)
+            })
+        })
+}
+
+/// A model found by trying every assignment, or None if there is none
+pub fn brute_force(instance: &Instance) -> Option<Vec<i32>> {
+    models(instance).next()
+}
+
+/// Some combination of the search settings
+pub fn random_options(rng: &mut Rng) -> SolverOptions {
+    let heuristic = HeuristicKind::NAMES[rng.below(HeuristicKind::NAMES.len())];
+    let restart = RestartPolicy::NAMES[rng.below(RestartPolicy::NAMES.len())];
+    SolverOptions {
+        chrono_threshold: match rng.below(3) {
+            0 => None,
+            1 => Some(0),
+            _ => Some(DEFAULT_CHRONO_THRESHOLD),
+        },
+        heuristic: HeuristicKind::from_name(heuristic).unwrap(),
+        restart: RestartPolicy::from_name(restart).unwrap(),
+        reuse_trail: rng.coin(),
+    }
+}
+
+/// Runs the solver the way the binary does: preprocessing, with or without
+/// the eliminations, then search
+pub fn solve(instance: &Instance, options: &SolverOptions, eliminate: bool) -> CNFStatus {
+    let mut solver_state = SolverState::from_parsed_out(ParsedOut {
+        num_variables: instance.num_variables,
+        num_clauses: instance.clauses.len(),
+        clauses: instance
+            .clauses
+            .iter()
+            .map(|clause| clause.iter().map(|&n| Literal::from(n)).collect())
+            .collect(),
+    });
+    solver_state.set_options(options.clone());
+    let preprocessed = if eliminate {
+        solver_state.preprocess()
+    } else {
+        solver_state.preprocess_keep_models()
+    };
+    match preprocessed {
+        FormulaPreprocess::TrivialUNSAT => CNFStatus::UNSAT,
+        FormulaPreprocess::Ok => solve_with_assumptions(&mut solver_state, &[]),
+    }
+}
+
+/// `solve`, with a panic of the solver turned into an error carrying its message
+pub fn solve_caught(
+    instance: &Instance,
+    options: &SolverOptions,
+    eliminate: bool,
+) -> Result<CNFStatus, String> {
+    panic::catch_unwind(AssertUnwindSafe(|| solve(instance, options, eliminate))).map_err(
+        |payload| {
+            payload
+                .downcast_ref::<&str>()
+                .map(|message| message.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_default()
+        },
+    )
+}
+
+/// Solves `instance` and compares with the truth table: the answers have to
+/// agree and a model has to satisfy the formula as given. A panic of the
+/// solver is reported as an error too. The solver does not write proofs, so
+/// UNSAT answers are only checked against the truth table.
+pub fn differential_check(
+    instance: &Instance,
+    options: &SolverOptions,
+    eliminate: bool,
+) -> Result<(), String> {
+    let expected = brute_force(instance);
+    let result = solve_caught(instance, options, eliminate)
+        .map_err(|message| format!("solver panicked: {}", message))?;
+    match (result, expected) {
+        (CNFStatus::UNSAT, None) => Ok(()),
+        (CNFStatus::UNSAT, Some(model)) => Err(format!("UNSAT but {:?} is a model", model)),
+        (CNFStatus::SAT { model }, expected) => {
+            if model.len() != instance.num_variables {
+                return Err(format!("model {:?} has the wrong length", model));
+            }
+            match check_model(&instance.input_formula(), &model) {
+                Err(e) => Err(format!("invalid model {:?}: {}", model, e)),
+                Ok(Verdict::Falsified(clause)) => Err(format!(
+                    "model {:?} falsifies clause {:?}",
+                    model, clause.lits
+                )),
+                Ok(Verdict::Satisfied) if expected.is_none() => {
+                    Err(format!("model {:?} of an UNSAT formula", model))
+                }
+                Ok(Verdict::Satisfied) => Ok(()),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
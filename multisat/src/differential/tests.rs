@@ -0,0 +1,116 @@
+use super::*;
+
+/// Checks `instance` with both preprocessing modes and settings drawn from
+/// `rng`, failing with the formula so it can be replayed
+fn check(rng: &mut Rng, instance: &Instance, family: &str) {
+    for eliminate in [false, true] {
+        let options = random_options(rng);
+        if let Err(e) = differential_check(instance, &options, eliminate) {
+            panic!(
+                "{} with {:?}, eliminate {}: {}\n{}",
+                family,
+                options,
+                eliminate,
+                e,
+                instance.to_dimacs()
+            );
+        }
+    }
+}
+
+#[test]
+fn brute_force_decides_small_formulas() {
+    let sat = Instance {
+        num_variables: 3,
+        clauses: vec![vec![1, 2], vec![-1, 3], vec![-3, -2]],
+    };
+    assert_eq!(brute_force(&sat), Some(vec![-1, 2, -3]));
+    assert_eq!(
+        models(&sat).collect::<Vec<_>>(),
+        vec![vec![-1, 2, -3], vec![1, -2, 3]]
+    );
+    assert_eq!(brute_force(&Instance::pigeonhole(2)), None);
+    assert_eq!(brute_force(&Instance::default()), Some(vec![]));
+}
+
+#[test]
+fn random_ksat_near_threshold() {
+    let mut rng = Rng::new(0x1234);
+    for round in 0..300 {
+        let k = 2 + round % 3;
+        let num_variables = k + rng.below(MAX_BRUTE_FORCE_VARIABLES - k + 1);
+        let instance = Instance::random_ksat(&mut rng, num_variables, k);
+        check(&mut rng, &instance, "random k-SAT");
+    }
+}
+
+#[test]
+fn structured_families() {
+    let mut rng = Rng::new(0x5678);
+    for holes in 1..=3 {
+        check(&mut rng, &Instance::pigeonhole(holes), "pigeonhole");
+    }
+    for _ in 0..60 {
+        let num_variables = 3 + rng.below(MAX_BRUTE_FORCE_VARIABLES - 2);
+        let width = 2 + rng.below(3.min(num_variables - 1));
+        let constraints = 1 + rng.below(num_variables);
+        let instance = Instance::random_xor(&mut rng, num_variables, constraints, width);
+        check(&mut rng, &instance, "parity");
+    }
+    for _ in 0..60 {
+        let colors = 2 + rng.below(2);
+        let vertices = 2 + rng.below(MAX_BRUTE_FORCE_VARIABLES / colors - 1);
+        let edge_permille = 300 + rng.below(600);
+        let instance = Instance::coloring(&mut rng, vertices, colors, edge_permille);
+        check(&mut rng, &instance, "coloring");
+    }
+}
+
+#[test]
+fn messy_inputs_from_bytes() {
+    let mut rng = Rng::new(0x9abc);
+    for _ in 0..300 {
+        let len = 1 + rng.below(80);
+        let data: Vec<u8> = (0..len)
+            .map(|_| {
+                // short clauses are more interesting than long ones
+                if rng.below(4) == 0 {
+                    0
+                } else {
+                    rng.below(256) as u8
+                }
+            })
+            .collect();
+        let instance = Instance::from_bytes(&data);
+        assert!(instance.num_variables <= MAX_BRUTE_FORCE_VARIABLES);
+        assert!(instance
+            .clauses
+            .iter()
+            .flatten()
+            .all(|&lit| lit != 0 && lit.unsigned_abs() as usize <= instance.num_variables));
+        check(&mut rng, &instance, "bytes");
+    }
+}
+
+#[test]
+fn wrong_answers_are_caught() {
+    let instance = Instance {
+        num_variables: 2,
+        clauses: vec![vec![1, 2], vec![-1, 2], vec![1, -2], vec![-1, -2]],
+    };
+    assert_eq!(brute_force(&instance), None);
+    assert!(differential_check(&instance, &SolverOptions::default(), true).is_ok());
+    // drop a clause from what the solver sees, its model misses the last one
+    let weaker = Instance {
+        num_variables: 2,
+        clauses: instance.clauses[..3].to_vec(),
+    };
+    let model = match solve(&weaker, &SolverOptions::default(), true) {
+        CNFStatus::SAT { model } => model,
+        CNFStatus::UNSAT => panic!("formula is satisfiable"),
+    };
+    assert!(matches!(
+        check_model(&instance.input_formula(), &model),
+        Ok(Verdict::Falsified(_))
+    ));
+}
@@ -1,69 +1,173 @@
+pub mod blocked;
+pub mod clause_db;
+pub mod elim;
+pub mod equiv;
+pub mod inprocess;
+pub mod options;
+pub mod phases;
+pub mod probe;
+pub mod reconstruct;
+pub mod restart;
+pub mod subsume;
 pub mod utils;
+pub mod vivify;
+pub mod walk;
 
-use std::{collections::VecDeque, vec};
+use std::vec;
 
+pub use clause_db::*;
+pub use options::*;
 pub use utils::*;
 pub mod heuristic;
 use rustc_hash::FxHashSet;
 
-use self::heuristic::VSIDS;
+use self::heuristic::Heuristic;
+use self::inprocess::{Inprocessing, PREPROCESS_EFFORT};
+use self::phases::{Rephase, REPHASE_CYCLE};
+use self::reconstruct::ReconstructionStack;
+use self::restart::Restarts;
 
-pub enum FormulaPreprocess {
-    TrivialUNSAT,
-    Ok,
+/// Learned clauses with at most this LBD are never deleted
+const CORE_LBD: usize = 2;
+/// Learned clauses with at most this LBD survive a reduction if they were used since
+/// the previous one
+const TIER2_LBD: usize = 6;
+const CLAUSE_ACTIVITY_DECAY: f32 = 0.999;
+const CLAUSE_ACTIVITY_LIMIT: f32 = 1e20;
+/// Binary implication minimization is only tried on learned clauses up to this LBD
+const BINARY_MINIMIZE_LBD: usize = 6;
+/// Conflicts before the first rephase, the n-th rephase waits n + 1 times as long
+const REPHASE_INTERVAL: usize = 1000;
+/// Local search effort when rephasing, in flips per original clause
+const WALK_FLIPS_PER_CLAUSE: usize = 10;
+const WALK_MAX_FLIPS: usize = 100_000;
+const RNG_SEED: u64 = 0x5eed;
+
+/// Literals removed from learned clauses by each minimization technique
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct MinimizeStats {
+    /// Literals of the 1-UIP clauses before minimization
+    pub learnt_literals: usize,
+    pub shrunk: usize,
+    pub recursive: usize,
+    pub binary: usize,
 }
 
-#[derive(Debug)]
-struct UnitPropDS {
-    dq: VecDeque<Decision>,
-    seen: FxHashSet<Literal>,
+/// What the simplification passes removed so far
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SimplifyStats {
+    pub eliminated: usize,
+    pub subsumed: usize,
+    pub strengthened: usize,
+    pub failed_literals: usize,
+    /// Literals implied by both phases of a probed variable
+    pub common_implied: usize,
+    pub hyper_binary: usize,
+    /// Variables replaced by an equivalent literal
+    pub substituted: usize,
+    /// Blocked clauses removed
+    pub blocked: usize,
+    /// Clauses removed after covered literal addition made them blocked
+    pub covered: usize,
+    /// Clauses shortened by vivification
+    pub vivified: usize,
 }
-impl UnitPropDS {
-    fn new(num_vars: usize) -> Self {
-        Self {
-            dq: VecDeque::with_capacity(num_vars),
-            seen: FxHashSet::with_capacity_and_hasher(num_vars, Default::default()),
-        }
-    }
-    fn clear(&mut self) {
-        self.dq.clear();
-        self.seen.clear();
-    }
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum FormulaPreprocess {
+    TrivialUNSAT,
+    Ok,
 }
 
 #[derive(Debug)]
 pub struct SolverState {
-    decision_stack: Vec<Decision>,
+    /// Every assignment in the order it was made. A literal is assigned the moment
+    /// it is pushed here
+    trail: Vec<Decision>,
+    /// Trail position of the decision that opened each level
+    trail_lim: Vec<usize>,
+    /// Trail entries before this index have had their watches visited
+    prop_head: usize,
     pub assig: Assig,
     pub level: usize,
     watchlist: WatchList,
     pub num_variables: usize,
-    pub clauses: Vec<Clause>,
-    decision_heuristic: VSIDS,
-    unit_prop_ds: UnitPropDS,
-    clauses_since_deletion: f32,
-    min_num_conflict_restart: f32,
-    max_num_conflict_restart: f32,
-    cur_num_conflict_restart: f32,
+    pub clause_db: ClauseDB,
+    decision_heuristic: Heuristic,
+    /// Trail index of the assignment of each variable
+    trail_pos: Vec<usize>,
+    /// Scratch marks for conflict analysis, always cleared after use
+    seen: Vec<bool>,
+    /// Variables marked during the analysis of the current conflict
+    analyzed: Vec<LiteralSize>,
+    pub restarts: Restarts,
+    unsat_at_root: bool,
+    conflicts: usize,
+    /// Conflict count at which the learned clauses are reduced next
+    next_reduce: usize,
+    reduce_interval: usize,
+    reduce_interval_inc: usize,
+    clause_activity_inc: f32,
+    pub minimize_stats: MinimizeStats,
+    pub options: SolverOptions,
+    /// Conflicts after which only one level was undone
+    pub chrono_backtracks: usize,
+    /// Stable mode searches longer around the current assignment, focused mode
+    /// restarts more and reacts faster to new conflicts
+    stable: bool,
+    next_mode_switch: usize,
+    mode_switch_interval: usize,
+    pub mode_switches: usize,
+    rng: Rng,
+    /// Conflict count at which the saved phases are reset next
+    next_rephase: usize,
+    pub rephases: usize,
+    /// Clauses removed by simplifications that do not keep every model
+    pub reconstruction: ReconstructionStack,
+    pub simplify_stats: SimplifyStats,
+    /// When the simplification passes run during search
+    pub inprocessing: Inprocessing,
+    /// Watches visited by unit propagation so far, the budget of inprocessing
+    /// is measured in these
+    pub propagation_ticks: usize,
 }
 
 impl SolverState {
     fn make_new(num_vars: usize) -> Self {
-        let dstack: Vec<Decision> = Vec::with_capacity(num_vars);
-
         Self {
-            decision_stack: dstack,
+            trail: Vec::with_capacity(num_vars),
+            trail_lim: Vec::new(),
+            prop_head: 0,
             assig: Assig::new(num_vars),
             level: 0,
             watchlist: WatchList::new(num_vars), //Initialize later
             num_variables: num_vars,
-            clauses: Vec::new(),
-            decision_heuristic: VSIDS::new(num_vars),
-            unit_prop_ds: UnitPropDS::new(num_vars),
-            clauses_since_deletion: 0.0,
-            cur_num_conflict_restart: 16.0,
-            max_num_conflict_restart: 1024.0,
-            min_num_conflict_restart: 16.0,
+            clause_db: ClauseDB::new(),
+            decision_heuristic: Heuristic::new(num_vars),
+            trail_pos: vec![0; num_vars + 1],
+            seen: vec![false; num_vars + 1],
+            analyzed: Vec::new(),
+            restarts: Restarts::default(),
+            unsat_at_root: false,
+            conflicts: 0,
+            next_reduce: 2000,
+            reduce_interval: 2000,
+            reduce_interval_inc: 300,
+            clause_activity_inc: 1.0,
+            minimize_stats: MinimizeStats::default(),
+            options: SolverOptions::default(),
+            chrono_backtracks: 0,
+            stable: false,
+            next_mode_switch: 1000,
+            mode_switch_interval: 1000,
+            mode_switches: 0,
+            rng: Rng::new(RNG_SEED),
+            next_rephase: REPHASE_INTERVAL,
+            rephases: 0,
+            reconstruction: ReconstructionStack::new(),
+            simplify_stats: SimplifyStats::default(),
+            inprocessing: Inprocessing::new(),
+            propagation_ticks: 0,
         }
     }
 
@@ -75,234 +179,347 @@ impl SolverState {
         solver_state
     }
 
-    pub fn decision_stack_size(&self) -> usize {
-        self.decision_stack.len()
+    pub fn trail_size(&self) -> usize {
+        self.trail.len()
+    }
+
+    /// Adds a fresh variable (e.g. a selector for a retractable clause) and returns it
+    pub fn new_var(&mut self) -> LiteralSize {
+        self.num_variables += 1;
+        let var = self.num_variables;
+        self.assig.add_var();
+        self.watchlist.add_var();
+        self.trail_pos.push(0);
+        self.seen.push(false);
+        self.decision_heuristic.add_var(var);
+        var
+    }
+
+    pub fn backtrack_to_root(&mut self) {
+        if self.level > 0 {
+            self.backtrack_to_level(0);
+        }
     }
 
     pub fn pick_var(&mut self) -> Literal {
         self.decision_heuristic.pick_var(&self.assig)
     }
 
+    pub fn set_options(&mut self, options: SolverOptions) {
+        self.decision_heuristic
+            .set_kind(options.heuristic, &self.assig);
+        self.restarts = Restarts::new(options.restart);
+        self.options = options;
+    }
+
+    pub fn is_stable(&self) -> bool {
+        self.stable
+    }
+
+    /// Moves between stable and focused mode. Each phase lasts twice as many
+    /// conflicts as the previous one of the same kind.
+    fn switch_mode(&mut self) {
+        self.stable = !self.stable;
+        self.mode_switches += 1;
+        if !self.stable {
+            self.mode_switch_interval *= 2;
+        }
+        self.next_mode_switch = self.conflicts + self.mode_switch_interval;
+        self.decision_heuristic.set_stable(self.stable, &self.assig);
+    }
+
+    /// Literals of every live clause
+    pub fn clauses(&self) -> impl Iterator<Item = &[Literal]> + '_ {
+        self.clause_db.iter().map(|cref| self.clause_db.lits(cref))
+    }
+
     pub fn delete_satisified_clauses(&mut self, lit: &Literal) {
-        self.clauses.iter_mut().for_each(|clause| {
-            if clause.literals.contains(lit) {
-                clause.deleted = true;
+        for cref in self.clause_db.refs() {
+            if self.clause_db.lits(cref).contains(lit) {
+                self.clause_db.delete(cref);
             }
-        });
+        }
     }
+    /// Assigns the literal of `d` and pushes it on the trail. Its watches are
+    /// visited by the next call to `unit_prop`
     pub fn add_decision(&mut self, d: &Decision) {
-        assert!(!literal_falsified(&d.get_lit(), &self.assig));
+        debug_assert!(literal_unassigned(&d.get_lit(), &self.assig));
 
         match d {
             Decision::AssertUnit { lit } => {
+                debug_assert!(self.level == 0);
                 // println!("Adding decision {:?} lvl: {}", d, 0);
                 self.delete_satisified_clauses(lit);
-                self.assig.insert(lit.var, AssigInfo::new(lit.sign, 0));
+                self.assig.insert(lit.var(), AssigInfo::new(lit.sign(), 0));
             }
             Decision::Choice { lit } => {
                 self.level += 1;
+                self.trail_lim.push(self.trail.len());
                 // println!("Adding decision {:?} lvl: {}", d, self.level);
 
                 self.assig
-                    .insert(lit.var, AssigInfo::new(lit.sign, self.level));
-                self.decision_stack.push(d.clone());
+                    .insert(lit.var(), AssigInfo::new(lit.sign(), self.level));
             }
-            Decision::UnitProp { lit, unit_prop_idx } => {
-                assert!(*unit_prop_idx < self.clauses.len());
+            Decision::UnitProp { lit, reason } => {
+                debug_assert!(!self.clause_db.is_deleted(*reason));
                 // println!("Adding decision {:?} lvl: {}", d, self.level);
-
+                let level = if self.options.chrono_threshold.is_some() {
+                    self.implied_level(lit, *reason)
+                } else {
+                    self.level
+                };
                 self.assig
-                    .insert(lit.var, AssigInfo::new(lit.sign, self.level));
-                if self.level != 0 {
-                    self.decision_stack.push(d.clone());
-                }
+                    .insert(lit.var(), AssigInfo::new(lit.sign(), level));
             }
         }
+        self.decision_heuristic
+            .assign_var(d.get_lit().var(), &self.assig);
+        self.trail_pos[d.get_lit().var()] = self.trail.len();
+        self.trail.push(d.clone());
     }
     pub fn add_decision_prop(&mut self, d: &Decision) -> FormulaUnitProp {
         self.add_decision(d);
-        self.unit_prop(d)
+        self.unit_prop()
     }
 
+    /// Undoes every assignment above `backtrack_level`. With chronological
+    /// backtracking the trail can hold literals implied at a lower level than the
+    /// one they were found at; those are kept and propagated again.
     pub fn backtrack_to_level(&mut self, backtrack_level: usize) {
         assert!(backtrack_level < self.level);
 
-        while self.level != backtrack_level {
-            self.pop_decision();
+        let trail_pos = self.trail_lim[backtrack_level];
+        let mut kept: Vec<Decision> = Vec::new();
+        for dec in self.trail.drain(trail_pos..) {
+            // println!("Popping decision {:?} lvl: {}", dec, self.level);
+            let var = dec.get_lit().var();
+            if self.assig.get(&var).unwrap().level <= backtrack_level {
+                kept.push(dec);
+            } else {
+                self.assig.remove(&var);
+                self.decision_heuristic.unassign(dec.get_lit(), &self.assig);
+            }
+        }
+        for dec in kept {
+            self.trail_pos[dec.get_lit().var()] = self.trail.len();
+            self.trail.push(dec);
         }
+        self.trail_lim.truncate(backtrack_level);
+        self.level = backtrack_level;
+        // everything below the cut was propagated before the next decision was made
+        self.prop_head = self.prop_head.min(trail_pos);
     }
 
-    fn pop_decision(&mut self) -> Decision {
-        match self.decision_stack.pop() {
-            Some(dec) => {
-                // println!("Popping decision {:?} lvl: {}", dec, self.level);
-                match dec {
-                    Decision::AssertUnit { .. } => unreachable!(),
-                    Decision::Choice { lit } => {
-                        self.level -= 1;
-                        self.assig.remove(&lit.var);
-                        dec
-                    }
-                    Decision::UnitProp { lit, .. } => {
-                        self.assig.remove(&lit.var);
-                        dec
-                    }
-                }
-            }
-            None => unreachable!(),
-        }
+    /// Stores a clause and watches its first two literals. Either they must be
+    /// unassigned, or the falsifying assignments must not have been propagated yet.
+    pub fn add_clause(&mut self, lits: &[Literal], learned: bool, lbd: usize) -> ClauseRef {
+        debug_assert!(self.check_clause_lits_unique(lits));
+        let cref = self.clause_db.add(lits, learned, lbd);
+        self.watchlist.watch_clause(lits, cref);
+        cref
     }
-    pub fn add_clause(&mut self, mut clause: Clause) {
+    pub fn check_clause_lits_unique(&self, clause: &[Literal]) -> bool {
         let clauseset = clause
-            .literals
             .iter()
-            .map(|lit| lit.var)
+            .map(|lit| lit.var())
             .collect::<FxHashSet<LiteralSize>>();
-        assert!(clauseset.len() == clause.literals.len());
-        clause.set_unassigned_watches(&self.assig);
-        self.watchlist
-            .add_to_list(&clause.literals[clause.w1], self.clauses.len());
-        self.watchlist
-            .add_to_list(&clause.literals[clause.w2], self.clauses.len());
-        self.clauses.push(clause);
-    }
-    pub fn check_clause_lits_unique(&self, clause: &Clause) -> bool {
-        let clauseset = clause
-            .literals
-            .iter()
-            .map(|lit| lit.var)
-            .collect::<FxHashSet<LiteralSize>>();
-        debug_assert!(clauseset.len() == clause.literals.len());
+        debug_assert!(clauseset.len() == clause.len());
         true
     }
 
-    pub fn add_conflict_clause(&mut self, mut clause: Clause, uip: Literal) {
-        debug_assert!(self.check_clause_lits_unique(&clause));
-
-        self.clauses_since_deletion += 1.0;
-
-        clause.w1 = clause
-            .literals
-            .iter()
-            .position(|&lit| {
-                self.assig
-                    .get(&lit.var)
-                    .is_some_and(|x| x.level == self.level)
-            })
-            .unwrap();
-        clause.w2 = clause.literals.iter().position(|&lit| lit == uip).unwrap();
-        self.decision_heuristic.add_clause(&clause);
-        debug_assert!(clause.literals[clause.w2] == uip);
+    /// Adds a learned clause whose asserting literal is at index 0 and whose
+    /// other literal from the backtrack level is at index 1
+    pub fn add_conflict_clause(&mut self, clause_lits: &[Literal], lbd: usize) -> ClauseRef {
+        debug_assert!(self.check_new_clause(clause_lits));
 
-        self.watchlist
-            .add_to_list(&clause.literals[clause.w1], self.clauses.len());
-        self.watchlist
-            .add_to_list(&clause.literals[clause.w2], self.clauses.len());
-
-        self.clauses.push(clause);
+        self.decision_heuristic.learned_clause(clause_lits);
+        self.add_clause(clause_lits, true, lbd)
     }
     pub fn add_raw_clause(&mut self, mut raw_clause: Vec<Literal>) -> bool {
         let mut set: FxHashSet<Literal> = FxHashSet::default();
         raw_clause.retain(|e| set.insert(*e));
+        if raw_clause.iter().any(|lit| set.contains(&lit.invert())) {
+            // tautologies are always satisfied
+            return !self.unsat_at_root;
+        }
 
-        if raw_clause.len() == 1 {
+        if raw_clause.is_empty() {
+            self.unsat_at_root = true;
+        } else if raw_clause.len() == 1 {
             let unit: Literal = raw_clause[0];
-            let d = Decision::make_assertunit(unit);
-            self.add_decision(&d);
+            if literal_falsified(&unit, &self.assig) {
+                self.unsat_at_root = true;
+            } else if literal_unassigned(&unit, &self.assig) {
+                let d = Decision::make_assertunit(unit);
+                self.add_decision(&d);
+            }
         } else {
-            let clause = Clause::try_from(raw_clause).unwrap();
-            self.add_clause(clause);
+            self.add_clause(&raw_clause, false, 0);
         }
-        true
-    }
-    pub fn num_clauses(&self) -> usize {
-        self.clauses.len()
+        !self.unsat_at_root
     }
 
-    pub fn unit_prop(&mut self, blame: &Decision) -> FormulaUnitProp {
-        self.unit_prop_ds.clear();
+    /// Adds a clause between two searches. Literals already fixed at level 0 are
+    /// simplified away and units are propagated right away. Returns false if the
+    /// formula became unsatisfiable.
+    pub fn add_clause_at_root(&mut self, mut raw_clause: Vec<Literal>) -> bool {
+        self.backtrack_to_root();
+        if raw_clause
+            .iter()
+            .any(|lit| literal_satisfied(lit, &self.assig))
+        {
+            return !self.unsat_at_root;
+        }
+        let mut set: FxHashSet<Literal> = FxHashSet::default();
+        raw_clause.retain(|e| !literal_falsified(e, &self.assig) && set.insert(*e));
+        if raw_clause.iter().any(|lit| set.contains(&lit.invert())) {
+            return !self.unsat_at_root;
+        }
 
-        self.unit_prop_ds.dq.push_back(blame.clone());
+        match raw_clause.len() {
+            0 => self.unsat_at_root = true,
+            1 => {
+                let d = Decision::make_assertunit(raw_clause[0]);
+                self.add_decision(&d);
+                if let FormulaUnitProp::Conflict { .. } = self.unit_prop() {
+                    self.unsat_at_root = true;
+                }
+            }
+            _ => {
+                self.decision_heuristic.add_clause(&raw_clause);
+                self.add_clause(&raw_clause, false, 0);
+            }
+        }
+        !self.unsat_at_root
+    }
 
-        // maintain a seperate set from the assignments because we want the chronology to be correct -
-        // all parents lits in the implication graph should precede the given lit without ^ that would break
-        self.unit_prop_ds.seen.insert(blame.get_lit());
+    pub fn is_unsat_at_root(&self) -> bool {
+        self.unsat_at_root
+    }
+    pub fn num_clauses(&self) -> usize {
+        self.clause_db.len()
+    }
 
-        let mut add_unit: bool = false;
-        while !self.unit_prop_ds.dq.is_empty() {
-            let d = self.unit_prop_ds.dq.pop_front().unwrap();
-            let unit = d.get_lit();
+    /// Visits the watches of every trail literal from `prop_head` on. Implied
+    /// literals are pushed on the trail as soon as they are found, so the trail
+    /// order always matches the implication graph.
+    pub fn unit_prop(&mut self) -> FormulaUnitProp {
+        while self.prop_head < self.trail.len() {
+            let unit = self.trail[self.prop_head].get_lit();
+            self.prop_head += 1;
             let unit_inverted = unit.invert();
-
-            if add_unit {
-                self.add_decision(&d);
-            }
             debug_assert!(literal_satisfied(&unit, &self.assig));
 
+            // watches that stay are compacted to the front of the list
+            let mut watches = self.watchlist.take_lit(&unit_inverted);
+            let mut keep = 0;
             let mut watch_idx = 0;
-            while watch_idx < self.watchlist.get_lit(&unit_inverted).len() {
-                let clause_idx = self.watchlist.get_lit(&unit_inverted)[watch_idx];
-                let clause = &mut self.clauses[clause_idx];
-                debug_assert!(literal_falsified(&unit_inverted, &self.assig));
-
-                match clause.unit_prop(&self.assig, &unit_inverted) {
-                    ClauseUnitProp::Reassigned {
-                        old_watch,
-                        new_watch,
-                    } => {
-                        debug_assert!(clause.literals.iter().any(|x| *x == new_watch));
-                        self.watchlist.remove_watch(&old_watch, watch_idx);
-                        self.watchlist.add_to_list(&new_watch, clause_idx);
+            let mut conflict = None;
+            while watch_idx < watches.len() {
+                let watch = watches[watch_idx];
+                watch_idx += 1;
+                if literal_satisfied(&watch.blocker, &self.assig) {
+                    watches[keep] = watch;
+                    keep += 1;
+                    continue;
+                }
+                let cref = watch.cref;
+                if watch.binary {
+                    // the blocker is the only other literal
+                    watches[keep] = watch;
+                    keep += 1;
+                    if literal_falsified(&watch.blocker, &self.assig) {
+                        conflict = Some(cref);
+                        break;
+                    }
+                    self.add_decision(&Decision::make_unitprop(watch.blocker, cref));
+                    continue;
+                }
+                let clause = self.clause_db.lits_mut(cref);
+
+                match clause_unit_prop(clause, &self.assig, &unit_inverted) {
+                    ClauseUnitProp::Reassigned { new_watch } => {
+                        let blocker = clause[0];
+                        self.watchlist
+                            .add_to_list(&new_watch, Watch::new(blocker, cref, false));
                     }
                     ClauseUnitProp::Satisfied => {
-                        watch_idx += 1;
-                        continue;
+                        // remember the true literal so the clause is skipped next time
+                        watches[keep] = Watch::new(clause[0], cref, false);
+                        keep += 1;
                     }
                     ClauseUnitProp::Unit { lit } => {
-                        if !self.unit_prop_ds.seen.contains(&lit)
-                            && !self.assig.contains_key(&lit.var)
-                        {
-                            self.unit_prop_ds
-                                .dq
-                                .push_back(Decision::make_unitprop(lit, clause_idx));
-                            self.unit_prop_ds.seen.insert(lit);
-                        }
-
-                        watch_idx += 1;
-                        continue;
+                        watches[keep] = watch;
+                        keep += 1;
+                        self.add_decision(&Decision::make_unitprop(lit, cref));
                     }
                     ClauseUnitProp::Conflict => {
-                        return FormulaUnitProp::Conflict {
-                            conflict_cause_idx: clause_idx,
-                        };
+                        watches[keep] = watch;
+                        keep += 1;
+                        conflict = Some(cref);
+                        break;
                     }
                 }
             }
-            //After the first add all the rest
-            add_unit = true;
+            self.propagation_ticks += watch_idx;
+            while watch_idx < watches.len() {
+                watches[keep] = watches[watch_idx];
+                keep += 1;
+                watch_idx += 1;
+            }
+            watches.truncate(keep);
+            self.watchlist.put_lit(&unit_inverted, watches);
+
+            if let Some(cref) = conflict {
+                return FormulaUnitProp::Conflict {
+                    conflict_cause: cref,
+                };
+            }
         }
         FormulaUnitProp::Ok
     }
     pub fn reset_watchlist(&mut self) {
         self.watchlist.clear();
-        for (idx, clause) in self.clauses.iter_mut().enumerate() {
-            clause.set_unassigned_watches(&self.assig);
-            self.watchlist.add_to_list(&clause.literals[clause.w1], idx);
-            self.watchlist.add_to_list(&clause.literals[clause.w2], idx);
+        for cref in self.clause_db.refs() {
+            let clause = self.clause_db.lits_mut(cref);
+            set_unassigned_watches(clause, &self.assig);
+            self.watchlist.watch_clause(clause, cref);
         }
     }
-    pub fn reset_watch_keepcurrentwatch(&mut self) {
-        self.watchlist.clear();
-        for (idx, clause) in self.clauses.iter_mut().enumerate() {
-            self.watchlist.add_to_list(&clause.literals[clause.w1], idx);
-            self.watchlist.add_to_list(&clause.literals[clause.w2], idx);
+    /// Frees deleted clauses and moves the watches and trail reasons to the new
+    /// references. Level 0 reasons may have been deleted, those assignments are
+    /// turned into asserted units since analysis never looks at them.
+    fn collect_garbage(&mut self) {
+        let reloc = self.clause_db.collect_garbage();
+        for wlist in self.watchlist.watchlist.iter_mut() {
+            wlist.retain_mut(|watch| match reloc.get(watch.cref) {
+                Some(cref) => {
+                    watch.cref = cref;
+                    true
+                }
+                None => false,
+            });
+        }
+        for dec in self.trail.iter_mut() {
+            if let Decision::UnitProp { lit, reason } = *dec {
+                *dec = match reloc.get(reason) {
+                    Some(reason) => Decision::make_unitprop(lit, reason),
+                    None => {
+                        debug_assert!(self.assig.get(&lit.var()).unwrap().level == 0);
+                        Decision::make_assertunit(lit)
+                    }
+                };
+            }
         }
     }
-    fn remove_marked_clauses(&mut self) {
-        self.clauses.retain(|clause| !clause.deleted);
+    pub fn remove_marked_clauses(&mut self) {
+        assert!(self.level == 0);
+        self.collect_garbage();
         self.reset_watchlist();
     }
-    fn pure_literal_elimination(&mut self) {
+    /// Asserts pure literals. The clauses they satisfy are blocked, so
+    /// `eliminate_blocked` would remove them too, this is just cheaper.
+    pub fn pure_literal_elimination(&mut self) {
         let mut pure_var_tracker: Vec<[bool; 2]> = vec![[false, false]; self.num_variables + 1];
 
         for lit in self.assig.keys() {
@@ -313,12 +530,12 @@ impl SolverState {
             }
         }
         //Get all the pure lits
-        for clause in self.clauses.iter() {
-            for lit in clause.literals.iter() {
+        for clause in self.clauses() {
+            for lit in clause.iter() {
                 if lit.is_negative() {
-                    pure_var_tracker[lit.var][0] = true;
+                    pure_var_tracker[lit.var()][0] = true;
                 } else {
-                    pure_var_tracker[lit.var][1] = true;
+                    pure_var_tracker[lit.var()][1] = true;
                 }
             }
         }
@@ -334,277 +551,740 @@ impl SolverState {
         //assign them
         for &pure_var in pure_vars.iter() {
             let sign = pure_var_tracker[pure_var][1];
-            let lit = Literal {
-                var: pure_var,
-                sign,
-            };
-            self.add_decision(&Decision::AssertUnit { lit: lit });
+            let lit = Literal::new(pure_var, sign);
+            self.add_decision(&Decision::AssertUnit { lit });
         }
         println!("Assigned {} pure vars", pure_vars.len());
     }
 
+    /// Simplifies the formula before search. Pure literal, bounded variable and
+    /// blocked clause elimination keep satisfiability, models of the eliminated
+    /// variables and clauses are rebuilt by `get_model`.
     pub fn preprocess(&mut self) -> FormulaPreprocess {
-        assert!(self.decision_stack.is_empty());
-        let orig_len = self.clauses.len();
-        //Unit prop all the unit clauses and then remove them
-        let unit_vars: FxHashSet<Literal> = self
-            .assig
-            .keys()
-            .map(|var| Literal {
-                var,
-                sign: self.assig.get(&var).unwrap().litsign,
-            })
-            .collect();
+        self.simplify(true)
+    }
 
-        for &lit in unit_vars.iter() {
-            let unit = Decision::make_assertunit(lit);
-            assert!(literal_satisfied(&lit, &self.assig) || literal_unassigned(&lit, &self.assig));
-            if let FormulaUnitProp::Conflict { .. } = self.unit_prop(&unit) {
-                return FormulaPreprocess::TrivialUNSAT;
+    /// Preprocessing that keeps every model of the formula, for callers that solve
+    /// under assumptions or need more than one model
+    pub fn preprocess_keep_models(&mut self) -> FormulaPreprocess {
+        self.simplify(false)
+    }
+
+    fn simplify(&mut self, eliminate: bool) -> FormulaPreprocess {
+        assert!(self.level == 0);
+        if self.unsat_at_root {
+            return FormulaPreprocess::TrivialUNSAT;
+        }
+        // learned clauses, e.g. hyper-binary resolvents, are not counted
+        let orig_len = self.clause_db.len() - self.clause_db.num_learned();
+        //Unit prop all the unit clauses and then remove them
+        if let FormulaUnitProp::Conflict { .. } = self.unit_prop() {
+            self.unsat_at_root = true;
+            return FormulaPreprocess::TrivialUNSAT;
+        }
+        for cref in self.clause_db.refs() {
+            if clause_satisfied(self.clause_db.lits(cref), &self.assig) {
+                self.clause_db.delete(cref);
             }
         }
-        self.clauses
-            .retain(|clause| !clause.clause_satisfied(&self.assig));
-        self.pure_literal_elimination();
+        if eliminate {
+            self.pure_literal_elimination();
+            // pure literals falsify nothing, this only moves prop_head past them
+            let res = self.unit_prop();
+            debug_assert!(res == FormulaUnitProp::Ok);
+        }
         self.remove_marked_clauses();
-        self.reset_watchlist();
-        for clause in self.clauses.iter() {
-            self.decision_heuristic.add_clause(clause);
+        self.subsume(false, PREPROCESS_EFFORT);
+        self.probe(PREPROCESS_EFFORT);
+        if self.unsat_at_root {
+            return FormulaPreprocess::TrivialUNSAT;
         }
-        self.decision_heuristic.sort_var_order();
+        if eliminate {
+            self.simplify_stats.substituted += self.substitute_equivalences();
+            if self.unsat_at_root {
+                return FormulaPreprocess::TrivialUNSAT;
+            }
+            self.simplify_stats.eliminated += self.eliminate_variables(PREPROCESS_EFFORT);
+            if self.unsat_at_root {
+                return FormulaPreprocess::TrivialUNSAT;
+            }
+            self.eliminate_blocked(true);
+        }
+        self.inprocessing.allow_elimination = eliminate;
+        self.rebuild_after_rewrite();
 
         self.check_watch_invariant();
 
+        let now_len = self.clause_db.len() - self.clause_db.num_learned();
         println!(
             "Original : {} now : {} removed: {} ",
             orig_len,
-            self.clauses.len(),
-            orig_len - self.clauses.len()
+            now_len,
+            orig_len - now_len
         );
         FormulaPreprocess::Ok
     }
     fn get_lit_level(&self, lit: &Literal) -> usize {
-        self.assig.get(&lit.var).unwrap().level
+        self.assig.get(&lit.var()).unwrap().level
     }
 
-    pub fn get_backtrack_level(&self, lits: &[Literal]) -> usize {
-        let mut highest = None;
-        let mut second_highest = None;
-
-        for &value in lits.iter() {
-            let v_level = self.get_lit_level(&value);
-            match highest {
-                Some(h) if v_level > h => {
-                    second_highest = highest;
-                    highest = Some(v_level);
-                }
-                Some(h) if v_level < h => match second_highest {
-                    Some(sh) if v_level > sh => {
-                        second_highest = Some(v_level);
-                    }
-                    None => {
-                        second_highest = Some(v_level);
-                    }
-                    _ => {}
-                },
-                None => {
-                    highest = Some(v_level);
-                }
-                _ => {}
-            }
+    /// The clause that implied the assignment of `var`, None for decisions and units
+    fn reason(&self, var: LiteralSize) -> Option<ClauseRef> {
+        match self.trail[self.trail_pos[var]] {
+            Decision::UnitProp { reason, .. } => Some(reason),
+            _ => None,
         }
-        second_highest.unwrap_or(0)
     }
 
-    fn check_new_clause(&self, new_clause: &Clause) -> bool {
-        let clauseset: FxHashSet<Literal> = FxHashSet::from_iter(new_clause.literals.clone());
-        assert!(clauseset.len() == new_clause.literals.len());
+    /// Level of a literal implied by `reason`: the highest level among the other,
+    /// falsified literals of the clause
+    fn implied_level(&self, lit: &Literal, reason: ClauseRef) -> usize {
+        self.clause_db
+            .lits(reason)
+            .iter()
+            .filter(|other| other.var() != lit.var())
+            .map(|other| self.get_lit_level(other))
+            .max()
+            .unwrap_or(0)
+    }
+
+    fn abstract_level(&self, var: LiteralSize) -> u32 {
+        1 << (self.assig.get(&var).unwrap().level & 31)
+    }
+
+    fn check_new_clause(&self, new_clause: &[Literal]) -> bool {
+        let clauseset: FxHashSet<Literal> = FxHashSet::from_iter(new_clause.iter().cloned());
+        assert!(clauseset.len() == new_clause.len());
         assert_eq!(
             new_clause
-                .literals
                 .iter()
                 .filter(|lit| literal_falsified(lit, &self.assig))
                 .count(),
-            new_clause.literals.len() - 1
+            new_clause.len() - 1
         );
-        //Only 1 lit from current level
-        assert_eq!(
-            new_clause
-                .literals
+        //Only the asserting literal is unassigned
+        assert!(literal_unassigned(&new_clause[0], &self.assig));
+        true
+    }
+
+    fn check_conflict_clause(&self, conflict_clause: &[Literal]) -> bool {
+        assert!(
+            conflict_clause
                 .iter()
-                .filter(|lit| literal_unassigned(lit, &self.assig))
-                .count(),
-            1
+                .all(|lit| literal_falsified(lit, &self.assig)),
+            "{}",
+            print_non_falsified_lits(conflict_clause, &self.assig)
         );
         true
     }
 
-    fn create_conflict_clause(&self, clause_lits: Vec<Literal>, lbd: usize) -> Clause {
-        let uip_idx = clause_lits.len() - 1;
-        let new_clause = Clause {
-            literals: clause_lits,
-            w1: 0,
-            w2: uip_idx,
-            deleted: false,
-            conflict: true,
-            lbd: lbd,
-        };
-        debug_assert!(self.check_new_clause(&new_clause));
-        new_clause
+    /// Derives the first UIP clause by resolving backwards along the trail, starting
+    /// from the conflict clause, until a single literal of the current level is left.
+    /// Returns the clause with the negated UIP at index 0.
+    fn learn_first_uip(&mut self, conflict: ClauseRef) -> Vec<Literal> {
+        // index 0 is reserved for the negated UIP
+        let mut learnt: Vec<Literal> = vec![Literal::new(0, true)];
+        let mut cur_level_count = 0;
+        let mut reason = conflict;
+        let mut trail_idx = self.trail.len();
+        let mut resolved: Option<Literal> = None;
+
+        loop {
+            self.update_learned_clause(reason);
+            for &lit in self.clause_db.lits(reason).iter() {
+                if resolved.is_some_and(|p| p.var() == lit.var()) || self.seen[lit.var()] {
+                    continue;
+                }
+                let lit_level = self.get_lit_level(&lit);
+                if lit_level == 0 {
+                    continue;
+                }
+                self.seen[lit.var()] = true;
+                self.analyzed.push(lit.var());
+                if lit_level == self.level {
+                    cur_level_count += 1;
+                } else {
+                    learnt.push(lit);
+                }
+            }
+            // the most recent marked assignment of this level is the next one to
+            // resolve on, marked literals of lower levels can sit in between
+            loop {
+                trail_idx -= 1;
+                let lit = self.trail[trail_idx].get_lit();
+                if self.seen[lit.var()] && self.get_lit_level(&lit) == self.level {
+                    break;
+                }
+            }
+            let dec = &self.trail[trail_idx];
+            let p = dec.get_lit();
+            self.seen[p.var()] = false;
+            resolved = Some(p);
+            cur_level_count -= 1;
+            if cur_level_count == 0 {
+                break;
+            }
+            reason = match dec {
+                Decision::UnitProp { reason, .. } => *reason,
+                d => unreachable!("Got unexpected {:?} ", d),
+            };
+        }
+        learnt[0] = resolved.unwrap().invert();
+        for lit in learnt[1..].iter() {
+            self.seen[lit.var()] = false;
+        }
+        self.decision_heuristic
+            .conflict(&learnt, &mut self.analyzed, &self.assig);
+        self.analyzed.clear();
+        learnt
     }
-    fn check_conflict_clause(&self, conflict_clause: &Clause) {
-        print_non_falsified_lits(conflict_clause, &self.assig);
-        assert!(conflict_clause
-            .literals
-            .iter()
-            .all(|lit| literal_falsified(lit, &self.assig)));
+
+    /// Reports the variables that appear in the reasons of the learned clause's
+    /// literals without being in the clause themselves
+    fn reason_side_vars(&mut self, learnt: &[Literal]) {
+        for lit in learnt.iter() {
+            self.seen[lit.var()] = true;
+        }
+        let mut reason_side: Vec<LiteralSize> = Vec::new();
+        for lit in learnt.iter() {
+            let reason = match self.reason(lit.var()) {
+                Some(reason) => reason,
+                None => continue,
+            };
+            for other in self.clause_db.lits(reason).iter() {
+                if !self.seen[other.var()] && self.get_lit_level(other) > 0 {
+                    self.seen[other.var()] = true;
+                    reason_side.push(other.var());
+                }
+            }
+        }
+        for lit in learnt.iter() {
+            self.seen[lit.var()] = false;
+        }
+        for &var in reason_side.iter() {
+            self.seen[var] = false;
+        }
+        self.decision_heuristic.reason_side(&reason_side);
     }
-    pub fn analyze_conflict_backtrack(&mut self, conflict_idx: usize) -> ConflictAnalysisResult {
-        if self.level == 0 {
-            return ConflictAnalysisResult::UNSAT;
+
+    /// Shortens a 1-UIP clause with all-UIP shrinking, recursive minimization and
+    /// binary implication minimization. The asserting literal stays at index 0.
+    fn minimize_learnt(&mut self, learnt: &mut Vec<Literal>) {
+        self.minimize_stats.learnt_literals += learnt.len();
+        for lit in learnt[1..].iter() {
+            self.seen[lit.var()] = true;
         }
-        let conflict_clause = &self.clauses[conflict_idx];
 
-        self.check_conflict_clause(conflict_clause);
+        let before = learnt.len();
+        self.shrink_learnt(learnt);
+        self.minimize_stats.shrunk += before - learnt.len();
 
-        let blamed_decisions = conflict_clause.literals.iter().map(|lit| lit.invert());
+        let before = learnt.len();
+        let mut toclear: Vec<LiteralSize> = learnt[1..].iter().map(|lit| lit.var()).collect();
+        let abstract_levels = learnt[1..]
+            .iter()
+            .fold(0, |acc, lit| acc | self.abstract_level(lit.var()));
+        let mut idx = 1;
+        while idx < learnt.len() {
+            let lit = learnt[idx];
+            if self.reason(lit.var()).is_some()
+                && self.lit_redundant(lit, abstract_levels, &mut toclear)
+            {
+                learnt.swap_remove(idx);
+            } else {
+                idx += 1;
+            }
+        }
+        self.minimize_stats.recursive += before - learnt.len();
+        for var in toclear {
+            self.seen[var] = false;
+        }
 
-        let (mut curset, mut blamed_decs): (FxHashSet<Literal>, FxHashSet<Literal>) =
-            blamed_decisions.partition(|lit| self.assig.get(&lit.var).unwrap().level == self.level);
+        let before = learnt.len();
+        if self.compute_lbd(learnt) <= BINARY_MINIMIZE_LBD {
+            self.binary_minimize(learnt);
+        }
+        self.minimize_stats.binary += before - learnt.len();
+    }
 
-        debug_assert!(!curset.is_empty());
-        debug_assert!(
-            !curset.is_empty(),
-            "{}",
-            print_clause_lit_assigs(conflict_clause, &self.assig)
-        );
-        debug_assert!(curset
+    /// All-UIP shrinking: the literals of each lower level are replaced by the
+    /// single UIP of that level, as long as reaching it only needs literals that are
+    /// already in the clause. Levels are handled from the highest down, so the marks
+    /// of the lower levels always match the clause. Literals of the clause must be
+    /// marked in `seen` and stay marked on return.
+    fn shrink_learnt(&mut self, learnt: &mut Vec<Literal>) {
+        let mut levels: Vec<usize> = learnt[1..]
+            .iter()
+            .map(|lit| self.get_lit_level(lit))
+            .collect();
+        levels.sort_unstable_by(|a, b| b.cmp(a));
+        let mut shrinkable: Vec<usize> = Vec::new();
+        for pair in levels.windows(2) {
+            if pair[0] == pair[1] && shrinkable.last() != Some(&pair[0]) {
+                shrinkable.push(pair[0]);
+            }
+        }
+        for level in shrinkable {
+            let at_level: Vec<Literal> = learnt
+                .iter()
+                .skip(1)
+                .filter(|lit| self.get_lit_level(lit) == level)
+                .cloned()
+                .collect();
+            if let Some(uip) = self.shrink_level(level, &at_level) {
+                learnt.retain(|lit| !at_level.contains(lit));
+                for lit in at_level.iter() {
+                    self.seen[lit.var()] = false;
+                }
+                learnt.push(uip);
+                self.seen[uip.var()] = true;
+            }
+        }
+    }
+
+    /// Finds the UIP of `level` for the clause literals `at_level` by resolving
+    /// along the trail. Returns the falsified literal of the UIP, or None if a
+    /// resolution step would bring in a literal that is not in the clause.
+    fn shrink_level(&mut self, level: usize, at_level: &[Literal]) -> Option<Literal> {
+        let mut open = at_level.len();
+        let mut trail_idx = at_level
             .iter()
-            .all(|lit| self.assig.get(&lit.var).unwrap().level == self.level));
-        while curset.len() > 1 {
-            match self.pop_decision() {
-                Decision::UnitProp {
-                    lit,
-                    unit_prop_idx: unit_idx,
-                } => {
-                    let lit_present = curset.remove(&lit);
-                    if !lit_present {
+            .map(|lit| self.trail_pos[lit.var()])
+            .max()
+            .unwrap();
+        let mut added: Vec<LiteralSize> = Vec::new();
+        let mut uip = None;
+        'walk: loop {
+            let var = self.trail[trail_idx].get_lit().var();
+            if self.seen[var] && self.assig.get(&var).unwrap().level == level {
+                if open == 1 {
+                    uip = Some(self.trail[trail_idx].get_lit().invert());
+                    break;
+                }
+                open -= 1;
+                let reason = match self.reason(var) {
+                    Some(reason) => reason,
+                    None => break,
+                };
+                for &lit in self.clause_db.lits(reason).iter() {
+                    if lit.var() == var || self.seen[lit.var()] {
                         continue;
                     }
-                    for &negated_lit in self.clauses[unit_idx].literals.iter() {
-                        let decided_lit = negated_lit.invert();
-                        if decided_lit.var == lit.var {
-                            continue;
-                        }
-
-                        let resp_lit_level = self.get_lit_level(&decided_lit);
-
-                        if 0 < resp_lit_level && resp_lit_level < self.level {
-                            blamed_decs.insert(decided_lit);
-                        } else if resp_lit_level == self.level {
-                            curset.insert(decided_lit);
-                        }
+                    let lit_level = self.get_lit_level(&lit);
+                    if lit_level == level {
+                        self.seen[lit.var()] = true;
+                        added.push(lit.var());
+                        open += 1;
+                    } else if lit_level > 0 {
+                        break 'walk;
                     }
                 }
-                d => {
-                    print!("Level is {} Curset has: ", self.level);
-                    for lit in curset.iter() {
-                        print!(" {} ", print_lit_assig(lit, &self.assig));
+            }
+            trail_idx -= 1;
+        }
+        for var in added {
+            self.seen[var] = false;
+        }
+        uip
+    }
+
+    /// Checks if the falsified clause literal `p` is implied by the other clause
+    /// literals, following reasons depth first. Levels not in `abstract_levels` can
+    /// never lead back to the clause, so the search stops early on them. Every
+    /// variable marked along the way is pushed to `toclear`.
+    fn lit_redundant(
+        &mut self,
+        p: Literal,
+        abstract_levels: u32,
+        toclear: &mut Vec<LiteralSize>,
+    ) -> bool {
+        let mut stack = vec![p];
+        let top = toclear.len();
+        while let Some(q) = stack.pop() {
+            let reason = self.reason(q.var()).unwrap();
+            for &lit in self.clause_db.lits(reason).iter() {
+                let var = lit.var();
+                if var == q.var() || self.seen[var] || self.get_lit_level(&lit) == 0 {
+                    continue;
+                }
+                if self.reason(var).is_some() && self.abstract_level(var) & abstract_levels != 0 {
+                    self.seen[var] = true;
+                    stack.push(lit);
+                    toclear.push(var);
+                } else {
+                    for &var in toclear[top..].iter() {
+                        self.seen[var] = false;
                     }
-                    println!();
-                    unreachable!("Got unexpected {:?} ", d);
+                    toclear.truncate(top);
+                    return false;
                 }
             }
         }
-        //curset now has the UIP
-        let &uip = curset.iter().next().unwrap();
-        blamed_decs.insert(uip);
+        true
+    }
+
+    /// Removes `l` when a binary clause (learnt[0] or not l) exists, resolving it
+    /// away with that clause
+    fn binary_minimize(&mut self, learnt: &mut Vec<Literal>) {
+        let removable: Vec<Literal> = self
+            .watchlist
+            .get_lit(&learnt[0])
+            .iter()
+            .filter(|watch| watch.binary)
+            .map(|watch| watch.blocker.invert())
+            .collect();
+        if removable.is_empty() {
+            return;
+        }
+        let removable: FxHashSet<Literal> = removable.into_iter().collect();
+        let mut idx = 1;
+        while idx < learnt.len() {
+            if removable.contains(&learnt[idx]) {
+                learnt.swap_remove(idx);
+            } else {
+                idx += 1;
+            }
+        }
+    }
+
+    /// Bookkeeping of a conflict whose learned clause, or the conflict clause
+    /// itself, has the given LBD
+    fn count_conflict(&mut self, lbd: usize) {
+        self.restarts.on_conflict(lbd, self.trail.len());
+        self.conflicts += 1;
+        self.assig.tick();
+        self.clause_activity_inc /= CLAUSE_ACTIVITY_DECAY;
+        if self.conflicts >= self.next_mode_switch {
+            self.switch_mode();
+        }
+    }
+
+    /// Backtracks so a literal implied at `backtrack_level` can be assigned.
+    /// Chronological backtracking only goes back one level when the jump would
+    /// be longer than the threshold, the literal then stays above the levels in
+    /// between on the trail.
+    fn backtrack_for_implied(&mut self, backtrack_level: usize) {
+        match self.options.chrono_threshold {
+            Some(threshold) if self.level - backtrack_level > threshold => {
+                self.chrono_backtracks += 1;
+                self.backtrack_to_level(self.level - 1);
+            }
+            _ => self.backtrack_to_level(backtrack_level),
+        }
+    }
+
+    /// A conflict clause with a single literal of the conflict level already
+    /// asserts that literal at its second highest level (Nadel and Ryvchin).
+    /// Instead of learning a copy, backtracks and propagates the literal with
+    /// the conflict clause as its reason, which is rewatched on the literal and
+    /// the other one of the highest level. None if the clause needs analysis.
+    fn propagate_from_conflict(&mut self, conflict: ClauseRef) -> Option<Decision> {
+        let mut clause = self.clause_db.lits(conflict).to_vec();
+        if clause.len() < 2 {
+            return None;
+        }
+        let mut at_level =
+            (0..clause.len()).filter(|&idx| self.get_lit_level(&clause[idx]) == self.level);
+        let forced_idx = at_level.next()?;
+        if at_level.next().is_some() {
+            return None;
+        }
+        self.watchlist.unwatch_clause(&clause, conflict);
+        clause.swap(0, forced_idx);
+        let second_idx = (1..clause.len())
+            .max_by_key(|&idx| self.get_lit_level(&clause[idx]))
+            .unwrap();
+        clause.swap(1, second_idx);
+        self.clause_db.lits_mut(conflict).copy_from_slice(&clause);
+        self.watchlist.watch_clause(&clause, conflict);
+
+        self.update_learned_clause(conflict);
+        let lbd = self.compute_lbd(&clause);
+        self.count_conflict(lbd);
+        self.backtrack_for_implied(self.get_lit_level(&clause[1]));
+        let d = Decision::make_unitprop(clause[0], conflict);
+        self.add_decision(&d);
+        Some(d)
+    }
+
+    pub fn analyze_conflict_backtrack(&mut self, conflict: ClauseRef) -> ConflictAnalysisResult {
+        debug_assert!(self.check_conflict_clause(self.clause_db.lits(conflict)));
+        // out of order assignments can make a clause false below the current level
+        let conflict_level = self
+            .clause_db
+            .lits(conflict)
+            .iter()
+            .map(|lit| self.get_lit_level(lit))
+            .max()
+            .unwrap();
+        if conflict_level == 0 {
+            self.unsat_at_root = true;
+            return ConflictAnalysisResult::UNSAT;
+        }
+        if conflict_level < self.level {
+            self.backtrack_to_level(conflict_level);
+        }
+        // every level below the conflicting one propagated without conflict
+        self.decision_heuristic
+            .update_phases(&self.trail[..self.trail_lim[self.level - 1]]);
 
-        let clause_lits: Vec<Literal> = blamed_decs.into_iter().map(|lit| lit.invert()).collect();
+        if let Some(d) = self.propagate_from_conflict(conflict) {
+            return ConflictAnalysisResult::Backtrack { decision: d };
+        }
 
-        //calculate lbd
-        let mut leveltrack: FxHashSet<usize> = FxHashSet::default();
-        for lit in clause_lits.iter() {
-            leveltrack.insert(self.get_lit_level(lit));
+        let mut clause_lits = self.learn_first_uip(conflict);
+        self.minimize_learnt(&mut clause_lits);
+        if self.decision_heuristic.wants_reason_side() {
+            self.reason_side_vars(&clause_lits);
         }
-        let lbd = leveltrack.len();
 
-        let backtrack_level = self.get_backtrack_level(&clause_lits);
-        self.backtrack_to_level(backtrack_level);
+        let lbd = self.compute_lbd(&clause_lits);
+        self.count_conflict(lbd);
+
+        // the literal with the highest level after the UIP decides where to jump and
+        // becomes the second watch
+        let mut backtrack_level = 0;
+        if clause_lits.len() > 1 {
+            let mut max_idx = 1;
+            for idx in 2..clause_lits.len() {
+                if self.get_lit_level(&clause_lits[idx]) > self.get_lit_level(&clause_lits[max_idx])
+                {
+                    max_idx = idx;
+                }
+            }
+            clause_lits.swap(1, max_idx);
+            backtrack_level = self.get_lit_level(&clause_lits[1]);
+        }
+        let uip = clause_lits[0];
+        if clause_lits.len() > 1 {
+            self.backtrack_for_implied(backtrack_level);
+        } else {
+            self.backtrack_to_level(0);
+        }
         // println!("clause len size is {}", clause_lits.len());
         let d = if clause_lits.len() != 1 {
-            let new_clause = self.create_conflict_clause(clause_lits, lbd);
-            self.add_conflict_clause(new_clause, uip.invert());
-            Decision::make_unitprop(uip.invert(), self.clauses.len() - 1)
+            let cref = self.add_conflict_clause(&clause_lits, lbd);
+            Decision::make_unitprop(uip, cref)
         } else {
             assert_eq!(self.level, 0);
-            self.clauses
-                .retain(|clause| !clause.deleted && (!clause.conflict || clause.lbd <= 5));
-            self.reset_watch_keepcurrentwatch();
-            // println!("Old {} new {} clauses", curln, self.clauses.len());
-            Decision::make_assertunit(uip.invert())
+            Decision::make_assertunit(uip)
         };
         self.add_decision(&d);
         ConflictAnalysisResult::Backtrack { decision: d }
     }
 
-    pub fn restart_search(&mut self) {
-        if self.clauses_since_deletion > self.cur_num_conflict_restart {
-            self.backtrack_to_level(0);
-            self.clauses_since_deletion = 0.0;
-            if self.cur_num_conflict_restart <= self.max_num_conflict_restart {
-                self.cur_num_conflict_restart *= 2.0;
-            } else {
-                self.max_num_conflict_restart *= 1.2;
-                self.min_num_conflict_restart *= 1.2;
-                self.cur_num_conflict_restart = self.min_num_conflict_restart;
-                self.clauses.retain(|clause| clause.lbd <= 7);
-                self.reset_watch_keepcurrentwatch();
-                // println!("retained {} of {} clauses", self.clauses.len(), oldln);
-                debug_assert!(self.check_watch_invariant());
+    /// Restarts when the restart policy asks for it. With trail reuse the levels
+    /// whose decisions the heuristic would pick again anyway are kept. Returns
+    /// whether it restarted.
+    pub fn restart_search(&mut self) -> bool {
+        if !self.restarts.should_restart(self.stable) {
+            return false;
+        }
+        self.restarts.restarted(self.stable);
+        let level = if self.options.reuse_trail {
+            let decisions: Vec<LiteralSize> = self
+                .trail_lim
+                .iter()
+                .map(|&pos| self.trail[pos].get_lit().var())
+                .collect();
+            self.decision_heuristic
+                .reusable_levels(&decisions, &self.assig)
+        } else {
+            0
+        };
+        if level > 0 {
+            self.restarts.reused_trails += 1;
+        }
+        if level < self.level {
+            self.backtrack_to_level(level);
+        }
+        self.decision_heuristic.restart();
+        true
+    }
+
+    /// Reduces the learned clauses once enough conflicts have happened since the
+    /// last reduction
+    pub fn reduce_db_if_needed(&mut self) {
+        if self.conflicts >= self.next_reduce {
+            self.reduce_interval += self.reduce_interval_inc;
+            self.next_reduce = self.conflicts + self.reduce_interval;
+            self.reduce_db();
+        }
+    }
+
+    /// Resets the saved phases once enough conflicts have happened since the last
+    /// rephase, going through `REPHASE_CYCLE`. The interval grows with every
+    /// rephase.
+    pub fn rephase_if_needed(&mut self) {
+        if self.conflicts < self.next_rephase {
+            return;
+        }
+        let kind = REPHASE_CYCLE[self.rephases % REPHASE_CYCLE.len()];
+        self.rephases += 1;
+        self.next_rephase = self.conflicts + REPHASE_INTERVAL * (self.rephases + 1);
+        match kind {
+            Rephase::Walk => self.walk_phases(),
+            kind => self.decision_heuristic.rephase(kind, &mut self.rng),
+        }
+    }
+
+    /// Runs local search on the original clauses from the saved phases and saves
+    /// the best assignment it finds. Root assignments are fixed.
+    fn walk_phases(&mut self) {
+        let mut clauses: Vec<Vec<Literal>> = Vec::new();
+        for cref in self.clause_db.iter() {
+            if self.clause_db.is_learned(cref) {
+                continue;
             }
+            let lits = self.clause_db.lits(cref);
+            if lits
+                .iter()
+                .any(|lit| literal_satisfied(lit, &self.assig) && self.get_lit_level(lit) == 0)
+            {
+                continue;
+            }
+            clauses.push(
+                lits.iter()
+                    .filter(|lit| {
+                        !(literal_falsified(lit, &self.assig) && self.get_lit_level(lit) == 0)
+                    })
+                    .copied()
+                    .collect(),
+            );
+        }
+        let mut values: Vec<bool> = (0..=self.num_variables)
+            .map(|var| match self.assig.get(&var) {
+                Some(info) if info.level == 0 => info.litsign,
+                _ if var == 0 => false,
+                _ => self.decision_heuristic.saved_phase(var),
+            })
+            .collect();
+        let max_flips = (WALK_FLIPS_PER_CLAUSE * clauses.len()).min(WALK_MAX_FLIPS);
+        walk::walk(&clauses, &mut values, max_flips, &mut self.rng);
+        for (var, &sign) in values.iter().enumerate().skip(1) {
+            self.decision_heuristic.set_saved_phase(var, sign);
+        }
+    }
+
+    /// Deletes the less useful half of the local learned clauses. Core clauses are
+    /// always kept, tier2 clauses are kept while they keep being used and reasons
+    /// of current assignments are never deleted.
+    pub fn reduce_db(&mut self) {
+        let locked: FxHashSet<ClauseRef> = self
+            .trail
+            .iter()
+            .filter_map(|dec| match dec {
+                Decision::UnitProp { reason, .. } => Some(*reason),
+                _ => None,
+            })
+            .collect();
+        let mut local: Vec<ClauseRef> = Vec::new();
+        for cref in self.clause_db.refs() {
+            if !self.clause_db.is_learned(cref) {
+                continue;
+            }
+            let lbd = self.clause_db.lbd(cref);
+            let used = self.clause_db.is_used(cref);
+            self.clause_db.set_used(cref, false);
+            if lbd <= CORE_LBD || locked.contains(&cref) || (lbd <= TIER2_LBD && used) {
+                continue;
+            }
+            local.push(cref);
+        }
+        local.sort_by(|&a, &b| {
+            self.clause_db
+                .activity(a)
+                .total_cmp(&self.clause_db.activity(b))
+        });
+        for &cref in local[..local.len() / 2].iter() {
+            self.clause_db.delete(cref);
         }
+        self.collect_garbage();
+        debug_assert!(self.check_watch_invariant());
+    }
+
+    fn bump_clause_activity(&mut self, cref: ClauseRef) {
+        let activity = self.clause_db.activity(cref) + self.clause_activity_inc;
+        self.clause_db.set_activity(cref, activity);
+        if activity > CLAUSE_ACTIVITY_LIMIT {
+            for cref in self.clause_db.refs() {
+                let scaled = self.clause_db.activity(cref) / CLAUSE_ACTIVITY_LIMIT;
+                self.clause_db.set_activity(cref, scaled);
+            }
+            self.clause_activity_inc /= CLAUSE_ACTIVITY_LIMIT;
+        }
+    }
+
+    /// Marks a learned clause that took part in conflict analysis: its activity is
+    /// bumped and its LBD lowered if the current assignment gives a smaller one
+    fn update_learned_clause(&mut self, cref: ClauseRef) {
+        if !self.clause_db.is_learned(cref) {
+            return;
+        }
+        self.clause_db.set_used(cref, true);
+        self.bump_clause_activity(cref);
+        let old_lbd = self.clause_db.lbd(cref);
+        if old_lbd > CORE_LBD {
+            let lbd = self.compute_lbd(self.clause_db.lits(cref));
+            if lbd < old_lbd {
+                self.clause_db.set_lbd(cref, lbd);
+            }
+        }
+    }
+
+    /// Number of distinct decision levels among the literals
+    fn compute_lbd(&self, lits: &[Literal]) -> usize {
+        let mut levels: Vec<usize> = lits.iter().map(|lit| self.get_lit_level(lit)).collect();
+        levels.sort_unstable();
+        levels.dedup();
+        levels.len()
     }
 
     pub fn assigments_len(&self) -> usize {
         self.assig.len()
     }
 
+    /// Whether every variable that is still part of the formula is assigned
+    pub fn all_assigned(&self) -> bool {
+        self.assig.len() + self.assig.num_eliminated() == self.num_variables
+    }
+
+    /// The current assignment, extended to the eliminated variables
     pub fn get_model(&self) -> Vec<i32> {
-        let mut v: Vec<i32> = Vec::with_capacity(self.num_variables);
-        for var in 1..=self.num_variables {
-            let assigninfo = self.assig.get(&var).unwrap();
-            v.push(var as i32 * if assigninfo.litsign { 1 } else { -1 });
-        }
-        v
+        let mut values: Vec<bool> = (0..=self.num_variables)
+            .map(|var| self.assig.get(&var).is_some_and(|info| info.litsign))
+            .collect();
+        self.reconstruction.extend(&mut values);
+        (1..=self.num_variables)
+            .map(|var| var as i32 * if values[var] { 1 } else { -1 })
+            .collect()
     }
 
     pub fn check_watch_invariant(&self) -> bool {
-        for (idx, clause) in self.clauses.iter().enumerate() {
-            assert!(idx < self.clauses.len());
-
-            let l1 = clause.literals[clause.w1];
-            let l2 = clause.literals[clause.w2];
+        for cref in self.clause_db.iter() {
+            let clause = self.clause_db.lits(cref);
+            let l1 = clause[0];
+            let l2 = clause[1];
 
-            // assert!(self.watchlist.watchlist[l1.var].has(idx));
-            // assert!(self.watchlist.watchlist[l2.var].has(idx));
-            //both have to be unassigned or one of them has to be true
-            let invariant = (literal_unassigned(&l1, &self.assig)
-                && literal_unassigned(&l2, &self.assig))
-                || (literal_satisfied(&l1, &self.assig) || literal_satisfied(&l2, &self.assig));
+            assert!(self.watchlist.watches_clause(&l1, cref));
+            assert!(self.watchlist.watches_clause(&l2, cref));
+            //a watch can only be false if the clause is satisfied, possibly by a
+            //blocker that is not watched
+            let invariant = (!literal_falsified(&l1, &self.assig)
+                && !literal_falsified(&l2, &self.assig))
+                || clause_satisfied(clause, &self.assig);
 
             if !invariant {
-                println!("Idx is {} ", idx);
                 println!(
-                    "Failed at idx {} lits: {:?} assigs are {}:{:?} , {}:{:?}",
-                    idx,
-                    clause.literals,
+                    "Failed at {:?} lits: {:?} assigs are {}:{:?} , {}:{:?}",
+                    cref,
+                    clause,
                     l1,
-                    self.assig.get(&l1.var),
+                    self.assig.get(&l1.var()),
                     l2,
-                    self.assig.get(&l2.var)
+                    self.assig.get(&l2.var())
                 );
-                assert!(false);
+                panic!("watch invariant violated");
             }
         }
         true
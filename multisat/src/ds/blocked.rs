@@ -0,0 +1,201 @@
+use rustc_hash::FxHashSet;
+
+use crate::ds::clause_db::ClauseRef;
+use crate::ds::*;
+
+/// Literals whose negation occurs more often than this are not tried as the
+/// blocking literal
+const BLOCKED_OCC_LIMIT: usize = 100;
+/// Covered literal addition stops once the clause has grown to this size
+const COVER_CLAUSE_LIMIT: usize = 64;
+/// Literal visits one run may spend
+const BLOCKED_EFFORT: usize = 10_000_000;
+
+impl SolverState {
+    /// Blocked clause elimination, and covered clause elimination if `covered`
+    /// is set. A clause is blocked on one of its literals if every resolvent on
+    /// that literal is a tautology, so it can be removed and satisfied afterwards
+    /// by flipping the literal. Covered clause elimination first extends the
+    /// clause with the literals every non-tautological resolution partner
+    /// shares, which keeps it equivalent, and removes it if the extension
+    /// becomes blocked. A clause with a pure literal is the
+    /// simplest blocked clause. Only irredundant clauses take part, the removed
+    /// ones go on the reconstruction stack. Returns the number of removed clauses.
+    pub fn eliminate_blocked(&mut self, covered: bool) -> usize {
+        assert!(self.level == 0);
+        if self.unsat_at_root {
+            return 0;
+        }
+        let mut occurs: Vec<Vec<ClauseRef>> = vec![Vec::new(); 2 * (self.num_variables + 1)];
+        let mut queue: Vec<ClauseRef> = Vec::new();
+        for cref in self.clause_db.iter() {
+            if self.clause_db.is_learned(cref) {
+                continue;
+            }
+            let lits = self.clause_db.lits(cref);
+            if clause_satisfied(lits, &self.assig) {
+                continue;
+            }
+            for lit in lits.iter() {
+                occurs[lit.index()].push(cref);
+            }
+            if lits.iter().all(|lit| literal_unassigned(lit, &self.assig)) {
+                queue.push(cref);
+            }
+        }
+        // larger clauses are more likely blocked, the queue is taken from the end
+        queue.sort_by_key(|&cref| self.clause_db.clause_len(cref));
+        let mut queued: FxHashSet<ClauseRef> = queue.iter().copied().collect();
+
+        let mut marks: Vec<bool> = vec![false; 2 * (self.num_variables + 1)];
+        let mut effort = 0;
+        let mut removed = 0;
+        while let Some(cref) = queue.pop() {
+            queued.remove(&cref);
+            if effort > BLOCKED_EFFORT {
+                break;
+            }
+            if self.clause_db.is_deleted(cref) {
+                continue;
+            }
+            let clause = self.clause_db.lits(cref).to_vec();
+            let eliminated =
+                self.try_eliminate_blocked(cref, covered, &occurs, &mut marks, &mut effort);
+            if !eliminated {
+                continue;
+            }
+            removed += 1;
+            // partners of the removed clause lost one reason not to be blocked
+            for lit in clause.iter() {
+                for &partner in occurs[lit.invert().index()].iter() {
+                    if !self.clause_db.is_deleted(partner)
+                        && self
+                            .clause_db
+                            .lits(partner)
+                            .iter()
+                            .all(|lit| literal_unassigned(lit, &self.assig))
+                        && queued.insert(partner)
+                    {
+                        queue.push(partner);
+                    }
+                }
+            }
+        }
+        if removed > 0 {
+            self.finish_simplification(&[]);
+        }
+        removed
+    }
+
+    /// Checks one clause and removes it if it is blocked (or covered). `marks`
+    /// is all false before and after.
+    fn try_eliminate_blocked(
+        &mut self,
+        cref: ClauseRef,
+        covered: bool,
+        occurs: &[Vec<ClauseRef>],
+        marks: &mut [bool],
+        effort: &mut usize,
+    ) -> bool {
+        let mut extended: Vec<Literal> = self.clause_db.lits(cref).to_vec();
+        for lit in extended.iter() {
+            marks[lit.index()] = true;
+        }
+        // covered literal additions so far: the literal they were found on and
+        // the length of the clause before
+        let mut steps: Vec<(Literal, usize)> = Vec::new();
+        let mut blocked_on: Option<Literal> = None;
+        let mut changed = true;
+        'extend: while changed {
+            changed = false;
+            let mut idx = 0;
+            while idx < extended.len() {
+                let lit = extended[idx];
+                idx += 1;
+                if !literal_unassigned(&lit, &self.assig)
+                    || occurs[lit.invert().index()].len() > BLOCKED_OCC_LIMIT
+                {
+                    continue;
+                }
+                // literals every non-tautological partner has, None before the first
+                let mut shared: Option<Vec<Literal>> = None;
+                for &partner in occurs[lit.invert().index()].iter() {
+                    if self.clause_db.is_deleted(partner) {
+                        continue;
+                    }
+                    let partner_lits = self.clause_db.lits(partner);
+                    *effort += partner_lits.len();
+                    if clause_satisfied(partner_lits, &self.assig)
+                        || partner_lits
+                            .iter()
+                            .any(|&other| other != lit.invert() && marks[other.invert().index()])
+                    {
+                        continue;
+                    }
+                    if !covered {
+                        shared = Some(Vec::new());
+                        break;
+                    }
+                    shared = Some(match shared {
+                        None => partner_lits
+                            .iter()
+                            .copied()
+                            .filter(|&other| other != lit.invert() && !marks[other.index()])
+                            .collect(),
+                        Some(shared) => shared
+                            .into_iter()
+                            .filter(|other| partner_lits.contains(other))
+                            .collect(),
+                    });
+                    if shared.as_ref().is_some_and(|shared| shared.is_empty()) {
+                        break;
+                    }
+                }
+                match shared {
+                    None => {
+                        blocked_on = Some(lit);
+                        break 'extend;
+                    }
+                    Some(shared) if !shared.is_empty() => {
+                        if extended.len() + shared.len() > COVER_CLAUSE_LIMIT {
+                            continue;
+                        }
+                        // partners are not tautological with the clause, so
+                        // neither is the extension
+                        steps.push((lit, extended.len()));
+                        for other in shared {
+                            marks[other.index()] = true;
+                            extended.push(other);
+                        }
+                        changed = true;
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+        for lit in extended.iter() {
+            marks[lit.index()] = false;
+        }
+
+        let blocked_on = match blocked_on {
+            Some(blocked_on) => blocked_on,
+            None => return false,
+        };
+        // reconstruction handles the full extension first, then undoes the
+        // additions from the last to the first
+        for &(witness, len) in steps.iter() {
+            self.reconstruction.push(witness, &extended[..len]);
+        }
+        self.reconstruction.push(blocked_on, &extended);
+        if steps.is_empty() {
+            self.simplify_stats.blocked += 1;
+        } else {
+            self.simplify_stats.covered += 1;
+        }
+        self.clause_db.delete(cref);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests;
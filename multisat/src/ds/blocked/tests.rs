@@ -0,0 +1,57 @@
+use super::*;
+use crate::test_util::{assert_reconstructs, clauses, state};
+
+#[test]
+fn removes_blocked_clauses() {
+    // 1 2 is blocked on 1: its only partner -1 -2 3 resolves to a tautology
+    let original = clauses(&[&[1, 2], &[-1, -2, 3], &[3, 4], &[-3, -4]]);
+    let mut s = state(4, &original);
+    s.eliminate_blocked(false);
+    assert!(s.simplify_stats.blocked > 0);
+    assert!(s.num_clauses() < original.len());
+    assert_eq!(s.simplify_stats.covered, 0);
+    assert_reconstructs(&mut s, &original);
+}
+
+#[test]
+fn keeps_clauses_that_are_not_blocked() {
+    let original = clauses(&[&[1, 2], &[-1, 2], &[1, -2], &[-1, -2]]);
+    let mut s = state(2, &original);
+    assert_eq!(s.eliminate_blocked(true), 0);
+    assert_eq!(s.num_clauses(), original.len());
+}
+
+#[test]
+fn removes_covered_clauses() {
+    // No clause is blocked. The only partner of 1 in 1 2 adds 3, and then the
+    // only partner of 2 is a tautology.
+    let original = clauses(&[&[1, 2], &[-1, 3], &[-2, -3]]);
+    let mut s = state(3, &original);
+    assert_eq!(s.eliminate_blocked(false), 0);
+    assert!(s.eliminate_blocked(true) > 0);
+    assert!(s.simplify_stats.covered > 0);
+    assert_reconstructs(&mut s, &original);
+}
+
+#[test]
+fn preprocessing_with_covered_clauses_keeps_models() {
+    // Tseitin encoding of 7 = (1 & 2) | (3 ^ 4), with side constraints
+    let original = clauses(&[
+        &[-5, 1],
+        &[-5, 2],
+        &[5, -1, -2],
+        &[-6, 3, 4],
+        &[-6, -3, -4],
+        &[6, -3, 4],
+        &[6, 3, -4],
+        &[-7, 5, 6],
+        &[7, -5],
+        &[7, -6],
+        &[7],
+        &[-1, -3],
+        &[2, 4, -1],
+    ]);
+    let mut s = state(7, &original);
+    assert_eq!(s.preprocess(), FormulaPreprocess::Ok);
+    assert_reconstructs(&mut s, &original);
+}
@@ -0,0 +1,239 @@
+use std::fmt;
+
+use crate::ds::utils::*;
+
+/// Offset of a clause header in the arena
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ClauseRef(u32);
+
+impl fmt::Debug for ClauseRef {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "c{}", self.0)
+    }
+}
+
+const HEADER_LEN: usize = 3;
+const SIZE_WORD: usize = 0;
+const FLAGS_WORD: usize = 1;
+const ACTIVITY_WORD: usize = 2;
+
+const LEARNED_FLAG: u32 = 1;
+const DELETED_FLAG: u32 = 1 << 1;
+const USED_FLAG: u32 = 1 << 2;
+const LBD_SHIFT: u32 = 3;
+const MAX_LBD: u32 = u32::MAX >> LBD_SHIFT;
+
+/// All clauses stored back to back in one `u32` arena. Each clause is a header
+/// (size, flags + LBD, activity) followed by its literal codes. The watched
+/// literals of a clause are always its first two.
+///
+/// Deleting a clause only sets a flag, the space is reclaimed by `collect_garbage`.
+#[derive(Debug, Default)]
+pub struct ClauseDB {
+    arena: Vec<u32>,
+    num_clauses: usize,
+    num_learned: usize,
+    wasted: usize,
+}
+
+impl ClauseDB {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, lits: &[Literal], learned: bool, lbd: usize) -> ClauseRef {
+        assert!(lits.len() >= 2);
+        assert!(self.arena.len() + HEADER_LEN + lits.len() <= u32::MAX as usize);
+        let cref = ClauseRef(self.arena.len() as u32);
+        let flags =
+            if learned { LEARNED_FLAG } else { 0 } | ((lbd as u32).min(MAX_LBD) << LBD_SHIFT);
+        self.arena.push(lits.len() as u32);
+        self.arena.push(flags);
+        self.arena.push(0f32.to_bits());
+        self.arena.extend(lits.iter().map(|lit| lit.code()));
+        self.num_clauses += 1;
+        if learned {
+            self.num_learned += 1;
+        }
+        cref
+    }
+
+    #[inline(always)]
+    fn start(cref: ClauseRef) -> usize {
+        cref.0 as usize
+    }
+
+    #[inline(always)]
+    pub fn clause_len(&self, cref: ClauseRef) -> usize {
+        self.arena[Self::start(cref) + SIZE_WORD] as usize
+    }
+
+    #[inline(always)]
+    pub fn lits(&self, cref: ClauseRef) -> &[Literal] {
+        let start = Self::start(cref) + HEADER_LEN;
+        let words = &self.arena[start..start + self.clause_len(cref)];
+        // SAFETY: Literal is a repr(transparent) wrapper around its u32 code
+        unsafe { &*(words as *const [u32] as *const [Literal]) }
+    }
+
+    #[inline(always)]
+    pub fn lits_mut(&mut self, cref: ClauseRef) -> &mut [Literal] {
+        let start = Self::start(cref) + HEADER_LEN;
+        let end = start + self.clause_len(cref);
+        let words = &mut self.arena[start..end];
+        // SAFETY: Literal is a repr(transparent) wrapper around its u32 code
+        unsafe { &mut *(words as *mut [u32] as *mut [Literal]) }
+    }
+
+    fn flags(&self, cref: ClauseRef) -> u32 {
+        self.arena[Self::start(cref) + FLAGS_WORD]
+    }
+
+    pub fn is_learned(&self, cref: ClauseRef) -> bool {
+        self.flags(cref) & LEARNED_FLAG != 0
+    }
+
+    /// Turns a learned clause into an irredundant one, for a learned clause that
+    /// subsumed an irredundant one
+    pub fn set_irredundant(&mut self, cref: ClauseRef) {
+        if self.is_learned(cref) {
+            self.arena[Self::start(cref) + FLAGS_WORD] &= !LEARNED_FLAG;
+            self.num_learned -= 1;
+        }
+    }
+
+    pub fn is_deleted(&self, cref: ClauseRef) -> bool {
+        self.flags(cref) & DELETED_FLAG != 0
+    }
+
+    /// Set when the clause took part in conflict analysis since the last reduction
+    pub fn is_used(&self, cref: ClauseRef) -> bool {
+        self.flags(cref) & USED_FLAG != 0
+    }
+
+    pub fn set_used(&mut self, cref: ClauseRef, used: bool) {
+        let flags = &mut self.arena[Self::start(cref) + FLAGS_WORD];
+        if used {
+            *flags |= USED_FLAG;
+        } else {
+            *flags &= !USED_FLAG;
+        }
+    }
+
+    pub fn lbd(&self, cref: ClauseRef) -> usize {
+        (self.flags(cref) >> LBD_SHIFT) as usize
+    }
+
+    pub fn set_lbd(&mut self, cref: ClauseRef, lbd: usize) {
+        let flags = &mut self.arena[Self::start(cref) + FLAGS_WORD];
+        *flags = (*flags & ((1 << LBD_SHIFT) - 1)) | ((lbd as u32).min(MAX_LBD) << LBD_SHIFT);
+    }
+
+    pub fn activity(&self, cref: ClauseRef) -> f32 {
+        f32::from_bits(self.arena[Self::start(cref) + ACTIVITY_WORD])
+    }
+
+    pub fn set_activity(&mut self, cref: ClauseRef, activity: f32) {
+        self.arena[Self::start(cref) + ACTIVITY_WORD] = activity.to_bits();
+    }
+
+    pub fn delete(&mut self, cref: ClauseRef) {
+        if self.is_deleted(cref) {
+            return;
+        }
+        self.num_clauses -= 1;
+        if self.is_learned(cref) {
+            self.num_learned -= 1;
+        }
+        self.wasted += HEADER_LEN + self.clause_len(cref);
+        self.arena[Self::start(cref) + FLAGS_WORD] |= DELETED_FLAG;
+    }
+
+    /// Number of live clauses
+    pub fn len(&self) -> usize {
+        self.num_clauses
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.num_clauses == 0
+    }
+
+    pub fn num_learned(&self) -> usize {
+        self.num_learned
+    }
+
+    /// Arena words taken up by deleted clauses
+    pub fn wasted(&self) -> usize {
+        self.wasted
+    }
+
+    pub fn arena_len(&self) -> usize {
+        self.arena.len()
+    }
+
+    /// Every clause in the arena, deleted ones included
+    fn all_refs(&self) -> impl Iterator<Item = ClauseRef> + '_ {
+        let mut pos = 0;
+        std::iter::from_fn(move || {
+            if pos >= self.arena.len() {
+                return None;
+            }
+            let cref = ClauseRef(pos as u32);
+            pos += HEADER_LEN + self.arena[pos + SIZE_WORD] as usize;
+            Some(cref)
+        })
+    }
+
+    /// References of all live clauses in arena order
+    pub fn iter(&self) -> impl Iterator<Item = ClauseRef> + '_ {
+        self.all_refs().filter(|&cref| !self.is_deleted(cref))
+    }
+
+    pub fn refs(&self) -> Vec<ClauseRef> {
+        self.iter().collect()
+    }
+
+    /// Compacts the arena by dropping deleted clauses. References held outside the
+    /// database have to be moved with the returned `Relocation`.
+    pub fn collect_garbage(&mut self) -> Relocation {
+        let mut arena = Vec::with_capacity(self.arena.len() - self.wasted);
+        for cref in self.all_refs() {
+            if self.is_deleted(cref) {
+                continue;
+            }
+            let start = Self::start(cref);
+            arena.extend_from_slice(&self.arena[start..start + HEADER_LEN + self.clause_len(cref)]);
+        }
+        let mut old_arena = std::mem::replace(&mut self.arena, arena);
+        // the activity word of every old live header now forwards to its new place
+        let mut pos = 0;
+        let mut new_pos = 0;
+        while pos < old_arena.len() {
+            let len = HEADER_LEN + old_arena[pos + SIZE_WORD] as usize;
+            if old_arena[pos + FLAGS_WORD] & DELETED_FLAG == 0 {
+                old_arena[pos + ACTIVITY_WORD] = new_pos as u32;
+                new_pos += len;
+            }
+            pos += len;
+        }
+        self.wasted = 0;
+        Relocation { old_arena }
+    }
+}
+
+/// Maps clause references from before a garbage collection to the new arena
+pub struct Relocation {
+    old_arena: Vec<u32>,
+}
+
+impl Relocation {
+    /// The new reference of `cref`, or None if the clause was deleted
+    pub fn get(&self, cref: ClauseRef) -> Option<ClauseRef> {
+        let start = ClauseDB::start(cref);
+        if self.old_arena[start + FLAGS_WORD] & DELETED_FLAG != 0 {
+            None
+        } else {
+            Some(ClauseRef(self.old_arena[start + ACTIVITY_WORD]))
+        }
+    }
+}
@@ -0,0 +1,358 @@
+use rustc_hash::FxHashSet;
+
+use crate::ds::clause_db::ClauseRef;
+use crate::ds::*;
+
+/// Variables with more occurrences than this are not tried
+const ELIM_OCC_LIMIT: usize = 100;
+/// Eliminations that would add a resolvent longer than this are not done
+const ELIM_CLAUSE_LIMIT: usize = 100;
+/// XOR gates are looked for in clauses up to this size, they need 2^(size - 1)
+/// clauses
+const XOR_GATE_MAX_SIZE: usize = 5;
+
+/// Clauses of a definition of the eliminated variable, as indices into its
+/// positive and negative occurrences
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Gate {
+    pub pos: Vec<usize>,
+    pub neg: Vec<usize>,
+}
+
+/// Looks for clauses among the occurrences of `x` that define `x` as an AND
+/// (or OR), ITE or XOR of other literals. Resolving the gate clauses with the
+/// remaining ones is then enough to eliminate `x`, the other resolvents are
+/// implied.
+pub fn find_gate(x: Literal, pos: &[&[Literal]], neg: &[&[Literal]]) -> Option<Gate> {
+    find_and_gate(x, pos, neg)
+        .or_else(|| {
+            find_and_gate(x.invert(), neg, pos).map(|gate| Gate {
+                pos: gate.neg,
+                neg: gate.pos,
+            })
+        })
+        .or_else(|| find_ite_gate(x, pos, neg))
+        .or_else(|| {
+            find_ite_gate(x.invert(), neg, pos).map(|gate| Gate {
+                pos: gate.neg,
+                neg: gate.pos,
+            })
+        })
+        .or_else(|| find_xor_gate(x, pos, neg))
+}
+
+/// `p = l1 & ... & lk`: binary clauses `-p li` and the clause `p -l1 ... -lk`
+fn find_and_gate(p: Literal, pos: &[&[Literal]], neg: &[&[Literal]]) -> Option<Gate> {
+    // the other literal of each binary clause with -p
+    let implied: Vec<(Literal, usize)> = neg
+        .iter()
+        .enumerate()
+        .filter(|(_, clause)| clause.len() == 2)
+        .map(|(idx, clause)| (other_literal(clause, p.invert()), idx))
+        .collect();
+    for (idx, clause) in pos.iter().enumerate() {
+        let mut gate_neg = Vec::new();
+        for lit in clause.iter().filter(|&&lit| lit != p) {
+            match implied
+                .iter()
+                .find(|(implied_lit, _)| *implied_lit == lit.invert())
+            {
+                Some(&(_, neg_idx)) => gate_neg.push(neg_idx),
+                None => break,
+            }
+        }
+        if gate_neg.len() == clause.len() - 1 {
+            return Some(Gate {
+                pos: vec![idx],
+                neg: gate_neg,
+            });
+        }
+    }
+    None
+}
+
+/// `p = c ? t : e`: clauses `-p -c t`, `-p c e`, `p -c -t` and `p c -e`
+fn find_ite_gate(p: Literal, pos: &[&[Literal]], neg: &[&[Literal]]) -> Option<Gate> {
+    let ternary = |clauses: &[&[Literal]], lit: Literal| -> Vec<(usize, Literal, Literal)> {
+        clauses
+            .iter()
+            .enumerate()
+            .filter(|(_, clause)| clause.len() == 3)
+            .map(|(idx, clause)| {
+                let others: Vec<Literal> = clause.iter().copied().filter(|&l| l != lit).collect();
+                (idx, others[0], others[1])
+            })
+            .collect()
+    };
+    let pos_ternary = ternary(pos, p);
+    let neg_ternary = ternary(neg, p.invert());
+    let find = |clauses: &[(usize, Literal, Literal)], a: Literal, b: Literal| {
+        clauses
+            .iter()
+            .find(|&&(_, x, y)| (x == a && y == b) || (x == b && y == a))
+            .map(|&(idx, _, _)| idx)
+    };
+    for (i, &(idx1, a1, b1)) in neg_ternary.iter().enumerate() {
+        for &(idx2, a2, b2) in neg_ternary[i + 1..].iter() {
+            for (not_c, t) in [(a1, b1), (b1, a1)] {
+                for (c, e) in [(a2, b2), (b2, a2)] {
+                    if c != not_c.invert()
+                        || t.var() == e.var()
+                        || t.var() == c.var()
+                        || e.var() == c.var()
+                    {
+                        continue;
+                    }
+                    if let (Some(idx3), Some(idx4)) = (
+                        find(&pos_ternary, not_c, t.invert()),
+                        find(&pos_ternary, c, e.invert()),
+                    ) {
+                        return Some(Gate {
+                            pos: vec![idx3, idx4],
+                            neg: vec![idx1, idx2],
+                        });
+                    }
+                }
+            }
+        }
+    }
+    None
+}
+
+/// `x` is the XOR of the other variables of a clause: every clause over those
+/// variables with the same parity of negative literals is there
+fn find_xor_gate(x: Literal, pos: &[&[Literal]], neg: &[&[Literal]]) -> Option<Gate> {
+    let sorted = |clause: &[Literal]| {
+        let mut clause = clause.to_vec();
+        clause.sort_unstable();
+        clause
+    };
+    let pos_set: FxHashSet<Vec<Literal>> = pos.iter().map(|clause| sorted(clause)).collect();
+    let neg_set: FxHashSet<Vec<Literal>> = neg.iter().map(|clause| sorted(clause)).collect();
+    for clause in pos.iter() {
+        if clause.len() < 3 || clause.len() > XOR_GATE_MAX_SIZE {
+            continue;
+        }
+        let vars: Vec<LiteralSize> = clause.iter().map(|lit| lit.var()).collect();
+        let parity = clause.iter().filter(|lit| lit.is_negative()).count() % 2;
+        let mut gate_pos = Vec::new();
+        let mut gate_neg = Vec::new();
+        let mut complete = true;
+        for signs in 0u32..(1 << vars.len()) {
+            if (signs.count_ones() as usize) % 2 != parity {
+                continue;
+            }
+            // bit i set means the i-th variable is negative
+            let candidate: Vec<Literal> = vars
+                .iter()
+                .enumerate()
+                .map(|(i, &var)| Literal::new(var, signs & (1 << i) == 0))
+                .collect();
+            let candidate = sorted(&candidate);
+            let (set, side, gate) = if candidate.contains(&x) {
+                (&pos_set, pos, &mut gate_pos)
+            } else {
+                (&neg_set, neg, &mut gate_neg)
+            };
+            if !set.contains(&candidate) {
+                complete = false;
+                break;
+            }
+            gate.push(side.iter().position(|c| sorted(c) == candidate).unwrap());
+        }
+        if complete {
+            return Some(Gate {
+                pos: gate_pos,
+                neg: gate_neg,
+            });
+        }
+    }
+    None
+}
+
+fn other_literal(binary: &[Literal], lit: Literal) -> Literal {
+    if binary[0] == lit {
+        binary[1]
+    } else {
+        binary[0]
+    }
+}
+
+/// Resolvent of two clauses on `x`, without duplicates. None if it is a
+/// tautology.
+pub fn resolve(pos: &[Literal], neg: &[Literal], x: LiteralSize) -> Option<Vec<Literal>> {
+    let mut resolvent: Vec<Literal> = pos.iter().copied().filter(|lit| lit.var() != x).collect();
+    for &lit in neg.iter().filter(|lit| lit.var() != x) {
+        if resolvent.contains(&lit.invert()) {
+            return None;
+        }
+        if !resolvent.contains(&lit) {
+            resolvent.push(lit);
+        }
+    }
+    Some(resolvent)
+}
+
+impl SolverState {
+    /// Bounded variable elimination. A variable is resolved away when that adds no
+    /// more clauses than it removes; the removed clauses go on the reconstruction
+    /// stack. Only irredundant clauses are considered, learned clauses with an
+    /// eliminated variable are dropped. `effort` limits the literals of the
+    /// resolvents tried. Returns the number of eliminated variables.
+    pub fn eliminate_variables(&mut self, effort: usize) -> usize {
+        assert!(self.level == 0);
+        let mut occurs: Vec<Vec<ClauseRef>> = vec![Vec::new(); 2 * (self.num_variables + 1)];
+        for cref in self.clause_db.iter() {
+            if self.clause_db.is_learned(cref) {
+                continue;
+            }
+            for lit in self.clause_db.lits(cref) {
+                occurs[lit.index()].push(cref);
+            }
+        }
+        let mut candidates: Vec<LiteralSize> = (1..=self.num_variables)
+            .filter(|&var| self.assig.is_decidable(var))
+            .collect();
+        candidates.sort_by_key(|&var| {
+            occurs[Literal::new(var, true).index()].len()
+                * occurs[Literal::new(var, false).index()].len()
+        });
+
+        let mut units: Vec<Literal> = Vec::new();
+        // variables of new units stay until the units are asserted
+        let mut frozen: Vec<bool> = vec![false; self.num_variables + 1];
+        let mut eliminated = 0;
+        let mut spent = 0;
+        for var in candidates {
+            if spent > effort {
+                break;
+            }
+            if frozen[var] {
+                continue;
+            }
+            if self.try_eliminate(var, &mut occurs, &mut units, &mut frozen, &mut spent) {
+                eliminated += 1;
+            }
+            if self.unsat_at_root {
+                return eliminated;
+            }
+        }
+        if eliminated > 0 {
+            for cref in self.clause_db.refs() {
+                if self.clause_db.is_learned(cref)
+                    && self
+                        .clause_db
+                        .lits(cref)
+                        .iter()
+                        .any(|lit| self.assig.is_eliminated(lit.var()))
+                {
+                    self.clause_db.delete(cref);
+                }
+            }
+        }
+        self.finish_simplification(&units);
+        eliminated
+    }
+
+    fn try_eliminate(
+        &mut self,
+        var: LiteralSize,
+        occurs: &mut [Vec<ClauseRef>],
+        units: &mut Vec<Literal>,
+        frozen: &mut [bool],
+        spent: &mut usize,
+    ) -> bool {
+        let x = Literal::new(var, true);
+        for lit in [x, x.invert()] {
+            occurs[lit.index()].retain(|&cref| !self.clause_db.is_deleted(cref));
+        }
+        let pos_refs = occurs[x.index()].clone();
+        let neg_refs = occurs[x.invert().index()].clone();
+        if pos_refs.len() + neg_refs.len() > ELIM_OCC_LIMIT {
+            return false;
+        }
+        let pos: Vec<&[Literal]> = pos_refs
+            .iter()
+            .map(|&cref| self.clause_db.lits(cref))
+            .collect();
+        let neg: Vec<&[Literal]> = neg_refs
+            .iter()
+            .map(|&cref| self.clause_db.lits(cref))
+            .collect();
+        let gate = find_gate(x, &pos, &neg);
+        let in_gate =
+            |gate_idx: Option<&Vec<usize>>, idx: usize| gate_idx.is_some_and(|g| g.contains(&idx));
+
+        let mut resolvents: Vec<Vec<Literal>> = Vec::new();
+        for (i, p) in pos.iter().enumerate() {
+            for (j, n) in neg.iter().enumerate() {
+                *spent += p.len() + n.len();
+                if gate.is_some()
+                    && in_gate(gate.as_ref().map(|g| &g.pos), i)
+                        == in_gate(gate.as_ref().map(|g| &g.neg), j)
+                {
+                    continue;
+                }
+                let resolvent = match resolve(p, n, var) {
+                    Some(resolvent) => resolvent,
+                    None => continue,
+                };
+                // literals fixed at level 0 are simplified away
+                if resolvent
+                    .iter()
+                    .any(|lit| literal_satisfied(lit, &self.assig))
+                {
+                    continue;
+                }
+                let resolvent: Vec<Literal> = resolvent
+                    .into_iter()
+                    .filter(|lit| !literal_falsified(lit, &self.assig))
+                    .collect();
+                if resolvent.len() > ELIM_CLAUSE_LIMIT || resolvents.len() == pos.len() + neg.len()
+                {
+                    return false;
+                }
+                resolvents.push(resolvent);
+            }
+        }
+
+        // keep the smaller side, the other one is satisfied by the default value
+        let (kept, witness) = if pos.len() <= neg.len() {
+            (&pos, x)
+        } else {
+            (&neg, x.invert())
+        };
+        for clause in kept.iter() {
+            self.reconstruction.push(witness, clause);
+        }
+        self.reconstruction
+            .push(witness.invert(), &[witness.invert()]);
+        for &cref in pos_refs.iter().chain(neg_refs.iter()) {
+            self.clause_db.delete(cref);
+        }
+        self.assig.eliminate(var);
+
+        for resolvent in resolvents {
+            match resolvent.len() {
+                0 => {
+                    self.unsat_at_root = true;
+                    return true;
+                }
+                1 => {
+                    units.push(resolvent[0]);
+                    frozen[resolvent[0].var()] = true;
+                }
+                _ => {
+                    let cref = self.clause_db.add(&resolvent, false, 0);
+                    for lit in resolvent.iter() {
+                        occurs[lit.index()].push(cref);
+                    }
+                }
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,108 @@
+use super::*;
+use crate::test_util::{assert_reconstructs, assert_unsat, clauses, lits, state};
+
+fn slices(clauses: &[Vec<Literal>]) -> Vec<&[Literal]> {
+    clauses.iter().map(|clause| clause.as_slice()).collect()
+}
+
+#[test]
+fn resolve_drops_pivot_and_tautologies() {
+    assert_eq!(
+        resolve(&lits(&[1, 2]), &lits(&[-1, 3, 2]), 1),
+        Some(lits(&[2, 3]))
+    );
+    assert_eq!(resolve(&lits(&[1, 2]), &lits(&[-1, -2]), 1), None);
+}
+
+#[test]
+fn finds_and_gate() {
+    // 1 = 2 & 3
+    let pos = clauses(&[&[1, -2, -3], &[1, 4]]);
+    let neg = clauses(&[&[-1, 2], &[-1, 5, 6], &[-1, 3]]);
+    let gate = find_gate(Literal::from(1), &slices(&pos), &slices(&neg)).unwrap();
+    assert_eq!(
+        gate,
+        Gate {
+            pos: vec![0],
+            neg: vec![0, 2]
+        }
+    );
+}
+
+#[test]
+fn finds_or_gate_on_negative_side() {
+    // 1 = 2 | 3, i.e. -1 = -2 & -3
+    let pos = clauses(&[&[1, -2], &[1, -3]]);
+    let neg = clauses(&[&[-1, 2, 3]]);
+    let gate = find_gate(Literal::from(1), &slices(&pos), &slices(&neg)).unwrap();
+    assert_eq!(
+        gate,
+        Gate {
+            pos: vec![0, 1],
+            neg: vec![0]
+        }
+    );
+}
+
+#[test]
+fn finds_ite_gate() {
+    // 1 = 2 ? 3 : 4
+    let pos = clauses(&[&[1, -2, -3], &[1, 2, -4]]);
+    let neg = clauses(&[&[-1, -2, 3], &[-1, 2, 4]]);
+    let gate = find_gate(Literal::from(1), &slices(&pos), &slices(&neg)).unwrap();
+    assert_eq!(
+        gate,
+        Gate {
+            pos: vec![0, 1],
+            neg: vec![0, 1]
+        }
+    );
+}
+
+#[test]
+fn finds_xor_gate() {
+    // 1 = 2 ^ 3
+    let pos = clauses(&[&[1, 2, -3], &[1, -2, 3]]);
+    let neg = clauses(&[&[-1, 2, 3], &[-1, -2, -3], &[-1, 4]]);
+    let gate = find_gate(Literal::from(1), &slices(&pos), &slices(&neg)).unwrap();
+    assert_eq!(gate.pos.len(), 2);
+    assert_eq!(gate.neg, vec![0, 1]);
+}
+
+#[test]
+fn no_gate_in_plain_clauses() {
+    let pos = clauses(&[&[1, 2], &[1, 3, 4]]);
+    let neg = clauses(&[&[-1, 5]]);
+    assert_eq!(
+        find_gate(Literal::from(1), &slices(&pos), &slices(&neg)),
+        None
+    );
+}
+
+#[test]
+fn elimination_keeps_models_reconstructible() {
+    // 5 = 1 & 2 feeding into a small formula over 3 and 4
+    let original = clauses(&[
+        &[-5, 1],
+        &[-5, 2],
+        &[5, -1, -2],
+        &[5, 3],
+        &[-3, 4],
+        &[-4, -1, 2],
+        &[1, 3, -4],
+    ]);
+    let mut s = state(5, &original);
+    assert_eq!(s.preprocess(), FormulaPreprocess::Ok);
+    assert!(s.simplify_stats.eliminated > 0);
+    assert!(s
+        .clauses()
+        .all(|clause| clause.iter().all(|lit| !s.assig.is_eliminated(lit.var()))));
+    assert_reconstructs(&mut s, &original);
+}
+
+#[test]
+fn elimination_finds_unsat() {
+    let original = clauses(&[&[1, 2], &[-1, 2], &[1, -2], &[-1, -2, 3], &[-1, -2, -3]]);
+    let mut s = state(3, &original);
+    assert_unsat(&mut s);
+}
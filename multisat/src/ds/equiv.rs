@@ -0,0 +1,163 @@
+use crate::ds::*;
+
+/// Representative of every literal, indexed by literal, from the strongly
+/// connected components of the binary implication graph. Literals of a
+/// component imply each other and are equivalent; the one with the smallest
+/// variable represents them, so the component of the negations is represented by
+/// the negated representative. None if a literal is equivalent to its negation.
+pub fn representatives(binaries: &[[Literal; 2]], num_variables: usize) -> Option<Vec<Literal>> {
+    let num_lits = 2 * (num_variables + 1);
+    // a b is the implications -a -> b and -b -> a
+    let mut implies: Vec<Vec<usize>> = vec![Vec::new(); num_lits];
+    for &[a, b] in binaries.iter() {
+        implies[a.invert().index()].push(b.index());
+        implies[b.invert().index()].push(a.index());
+    }
+    let mut repr: Vec<Literal> = (0..num_lits as u32).map(Literal::from_code).collect();
+
+    // Tarjan's algorithm without recursion, `index` 0 means not visited yet
+    let mut index = vec![0usize; num_lits];
+    let mut lowlink = vec![0usize; num_lits];
+    let mut on_stack = vec![false; num_lits];
+    let mut stack: Vec<usize> = Vec::new();
+    let mut next_index = 1;
+    for start in 2..num_lits {
+        if index[start] != 0 {
+            continue;
+        }
+        // literal and the position of the next implication to follow
+        let mut path: Vec<(usize, usize)> = vec![(start, 0)];
+        index[start] = next_index;
+        lowlink[start] = next_index;
+        next_index += 1;
+        stack.push(start);
+        on_stack[start] = true;
+        while let Some(&(lit, next)) = path.last() {
+            if let Some(&succ) = implies[lit].get(next) {
+                path.last_mut().unwrap().1 += 1;
+                if index[succ] == 0 {
+                    index[succ] = next_index;
+                    lowlink[succ] = next_index;
+                    next_index += 1;
+                    stack.push(succ);
+                    on_stack[succ] = true;
+                    path.push((succ, 0));
+                } else if on_stack[succ] {
+                    lowlink[lit] = lowlink[lit].min(index[succ]);
+                }
+                continue;
+            }
+            path.pop();
+            if let Some(&(parent, _)) = path.last() {
+                lowlink[parent] = lowlink[parent].min(lowlink[lit]);
+            }
+            if lowlink[lit] != index[lit] {
+                continue;
+            }
+            let mut component = Vec::new();
+            loop {
+                let member = stack.pop().unwrap();
+                on_stack[member] = false;
+                component.push(member);
+                if member == lit {
+                    break;
+                }
+            }
+            if component.len() == 1 {
+                continue;
+            }
+            let min = *component.iter().min_by_key(|&&member| member >> 1).unwrap();
+            for &member in component.iter() {
+                if member == min ^ 1 {
+                    return None;
+                }
+                repr[member] = Literal::from_code(min as u32);
+            }
+        }
+    }
+    Some(repr)
+}
+
+impl SolverState {
+    /// Replaces equivalent literals by their representative in every clause. The
+    /// replaced variables count as eliminated, their equivalence goes on the
+    /// reconstruction stack. Returns the number of replaced variables.
+    pub fn substitute_equivalences(&mut self) -> usize {
+        assert!(self.level == 0);
+        if self.unsat_at_root {
+            return 0;
+        }
+        let binaries: Vec<[Literal; 2]> = self
+            .clause_db
+            .iter()
+            .map(|cref| self.clause_db.lits(cref))
+            .filter(|lits| {
+                lits.len() == 2 && lits.iter().all(|lit| literal_unassigned(lit, &self.assig))
+            })
+            .map(|lits| [lits[0], lits[1]])
+            .collect();
+        let repr = match representatives(&binaries, self.num_variables) {
+            Some(repr) => repr,
+            None => {
+                self.unsat_at_root = true;
+                return 0;
+            }
+        };
+
+        let mut substituted = 0;
+        for var in 1..=self.num_variables {
+            let x = Literal::new(var, true);
+            let r = repr[x.index()];
+            if r == x {
+                continue;
+            }
+            debug_assert!(self.assig.is_decidable(var));
+            self.reconstruction.push(x, &[x, r.invert()]);
+            self.reconstruction.push(x.invert(), &[x.invert(), r]);
+            self.assig.eliminate(var);
+            substituted += 1;
+        }
+        if substituted == 0 {
+            return 0;
+        }
+
+        let mut units: Vec<Literal> = Vec::new();
+        for cref in self.clause_db.refs() {
+            let lits = self.clause_db.lits(cref);
+            if lits.iter().all(|lit| repr[lit.index()] == *lit) {
+                continue;
+            }
+            let mut replaced: Vec<Literal> = Vec::with_capacity(lits.len());
+            let mut satisfied = false;
+            for lit in lits.iter().map(|lit| repr[lit.index()]) {
+                if replaced.contains(&lit.invert()) || literal_satisfied(&lit, &self.assig) {
+                    satisfied = true;
+                    break;
+                }
+                // merged literals can leave a clause with a single unassigned
+                // one, which has to become a unit
+                if !replaced.contains(&lit) && !literal_falsified(&lit, &self.assig) {
+                    replaced.push(lit);
+                }
+            }
+            let learned = self.clause_db.is_learned(cref);
+            let lbd = self.clause_db.lbd(cref).min(replaced.len());
+            self.clause_db.delete(cref);
+            if satisfied {
+                continue;
+            }
+            if replaced.is_empty() {
+                self.unsat_at_root = true;
+            } else if replaced.len() == 1 {
+                units.push(replaced[0]);
+            } else {
+                self.clause_db.add(&replaced, learned, lbd);
+            }
+        }
+        self.finish_simplification(&units);
+        substituted
+    }
+}
+
+#[cfg(test)]
+mod tests;
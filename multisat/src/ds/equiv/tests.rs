@@ -0,0 +1,70 @@
+use super::*;
+use crate::test_util::{assert_reconstructs, clauses, state};
+
+fn binaries(raw: &[[i32; 2]]) -> Vec<[Literal; 2]> {
+    raw.iter()
+        .map(|&[a, b]| [Literal::from(a), Literal::from(b)])
+        .collect()
+}
+
+#[test]
+fn cycle_is_represented_by_smallest_variable() {
+    // 3 -> -2 -> 4 -> 3, and 1 -> 5 outside the cycle
+    let repr = representatives(&binaries(&[[-3, -2], [2, 4], [-4, 3], [-1, 5]]), 5).unwrap();
+    let repr_of = |lit: i32| repr[Literal::from(lit).index()].to_dimacs();
+    assert_eq!(repr_of(3), -2);
+    assert_eq!(repr_of(4), -2);
+    assert_eq!(repr_of(-2), -2);
+    assert_eq!(repr_of(-3), 2);
+    assert_eq!(repr_of(-4), 2);
+    assert_eq!(repr_of(1), 1);
+    assert_eq!(repr_of(5), 5);
+}
+
+#[test]
+fn literal_equivalent_to_negation_is_inconsistent() {
+    // 1 -> 2 -> -1 -> -2 -> 1
+    assert_eq!(
+        representatives(&binaries(&[[-1, 2], [-2, -1], [1, -2], [2, 1]]), 2),
+        None
+    );
+}
+
+#[test]
+fn substitution_keeps_models_reconstructible() {
+    // 1 = 2 = -3, constrained further by longer clauses
+    let original = clauses(&[
+        &[-1, 2],
+        &[-2, 1],
+        &[2, 3],
+        &[-2, -3],
+        &[3, 4, 5],
+        &[-1, -4, 5],
+        &[1, -5, 4],
+        &[-3, -5, -4],
+    ]);
+    let mut s = state(5, &original);
+    assert_eq!(s.substitute_equivalences(), 2);
+    assert!(s.assig.is_eliminated(2) && s.assig.is_eliminated(3));
+    assert!(s
+        .clauses()
+        .all(|clause| clause.iter().all(|lit| lit.var() != 2 && lit.var() != 3)));
+    assert_reconstructs(&mut s, &original);
+}
+
+#[test]
+fn substitution_finds_unsat() {
+    let mut s = state(3, &clauses(&[&[-1, 2], &[-2, -1], &[1, -2], &[2, 1]]));
+    s.substitute_equivalences();
+    assert!(s.is_unsat_at_root());
+}
+
+#[test]
+fn merged_literals_with_false_rest_become_unit() {
+    // 1 = 2, and 3 is false, so 1 2 3 shrinks to the unit 1
+    let mut s = state(3, &clauses(&[&[-1, 2], &[1, -2], &[-3], &[1, 2, 3]]));
+    assert!(s.unit_prop() == FormulaUnitProp::Ok);
+    assert_eq!(s.substitute_equivalences(), 1);
+    assert!(literal_satisfied(&Literal::from(1), &s.assig));
+    assert!(s.check_watch_invariant());
+}
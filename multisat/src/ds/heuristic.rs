@@ -1,9 +1,54 @@
-use std::cmp::Ordering;
-use rustc_hash::FxHashMap;
-
+use std::fmt::Debug;
 
+use crate::ds::phases::{Phases, Rephase};
 use crate::ds::utils::*;
-#[derive(PartialEq,Eq,Clone,Debug,PartialOrd, Ord,Default)]
+
+/// A variable selection heuristic. The solver reports what happens during search
+/// through the hooks and asks `pick` for the next decision variable, the polarity
+/// is chosen separately by `Heuristic`. Only the active heuristic gets the hooks,
+/// `activate` brings it up to date when it takes over.
+pub trait DecisionHeuristic: Debug {
+    fn add_var(&mut self, var : LiteralSize);
+
+    /// Returns an unassigned variable, or None if every variable is assigned
+    fn pick(&mut self, assig : &Assig) -> Option<LiteralSize>;
+
+    fn on_assign(&mut self, _var : LiteralSize, _assig : &Assig) {}
+
+    /// `assig` no longer holds `var` but still knows when it was assigned
+    fn on_unassign(&mut self, var : LiteralSize, assig : &Assig);
+
+    /// `learnt` is the clause learned from the conflict, `analyzed` holds the
+    /// variables seen while deriving it
+    fn on_conflict(&mut self, learnt : &[Literal], analyzed : &mut [LiteralSize], assig : &Assig);
+
+    fn on_learned_clause(&mut self, _clause : &[Literal]) {}
+
+    /// Whether the solver should report the reason side of each learned clause,
+    /// collecting it costs a pass over the reasons
+    fn wants_reason_side(&self) -> bool {
+        false
+    }
+
+    /// Variables in the reasons of the learned clause's literals that are not in
+    /// the clause themselves
+    fn on_reason_side(&mut self, _vars : &[LiteralSize]) {}
+
+    fn on_restart(&mut self) {}
+
+    /// How many of the current decision levels a restart can keep, `decisions`
+    /// holds the decision variable of each level. Those levels would be rebuilt
+    /// the same way because their decisions are preferred over the variable that
+    /// would be picked next.
+    fn reusable_levels(&mut self, _decisions : &[LiteralSize], _assig : &Assig) -> usize {
+        0
+    }
+
+    /// Called when the heuristic starts making decisions after another one did
+    fn activate(&mut self, assig : &Assig);
+}
+
+#[derive(PartialEq,Eq,Clone,Debug,Default)]
 struct Phase {
     true_score : usize,
     false_score : usize,
@@ -16,132 +61,705 @@ impl Phase {
             self.true_score += bump;
         }
     }
-    pub fn decrement(&mut self) {
-        self.true_score -= 1;
-        self.false_score -= 1;
-    }
 }
 
-#[derive(PartialEq,Eq,Clone,Debug)]
-struct ScoreVar {
-    phase : Phase,
-    var : LiteralSize,
-    stored_phase : Option<bool>
+const ACTIVITY_LIMIT : f64 = 1e100;
+
+/// Binary max-heap of variables ordered by activity. `index` holds the heap
+/// position of every variable that is in the heap.
+#[derive(PartialEq,Debug,Default)]
+struct VarHeap {
+    heap : Vec<LiteralSize>,
+    index : Vec<Option<usize>>,
 }
 
-fn literal_from_score_var(var : &mut ScoreVar) -> Literal {
-    match var.stored_phase {
-        Some(true) => Literal::from(var.var as i32),
-        Some(false) => Literal::from(-(var.var as i32)),
-        None => {
-            if var.phase.true_score > var.phase.false_score {
-                var.stored_phase = Some(true);
-                Literal::from(var.var as i32)
-            } else {
-                var.stored_phase = Some(false);
-                Literal::from(-(var.var as i32))
+impl VarHeap {
+    fn contains(&self, var : LiteralSize) -> bool {
+        self.index[var].is_some()
+    }
+
+    fn swap(&mut self, a : usize, b : usize) {
+        self.heap.swap(a, b);
+        self.index[self.heap[a]] = Some(a);
+        self.index[self.heap[b]] = Some(b);
+    }
+
+    fn sift_up(&mut self, mut pos : usize, activity : &[f64]) {
+        while pos > 0 {
+            let parent = (pos - 1) / 2;
+            if activity[self.heap[pos]] <= activity[self.heap[parent]] {
+                break;
             }
+            self.swap(pos, parent);
+            pos = parent;
+        }
+    }
 
+    fn sift_down(&mut self, mut pos : usize, activity : &[f64]) {
+        loop {
+            let left = 2 * pos + 1;
+            if left >= self.heap.len() {
+                break;
+            }
+            let right = left + 1;
+            let child = if right < self.heap.len() && activity[self.heap[right]] > activity[self.heap[left]] { right } else { left };
+            if activity[self.heap[child]] <= activity[self.heap[pos]] {
+                break;
+            }
+            self.swap(pos, child);
+            pos = child;
         }
     }
-}
 
-impl PartialOrd for ScoreVar {
-    fn partial_cmp(&self, other: &ScoreVar) -> Option<Ordering> {
-        Some(self.cmp(other))
+    fn insert(&mut self, var : LiteralSize, activity : &[f64]) {
+        if self.contains(var) {
+            return;
+        }
+        self.index[var] = Some(self.heap.len());
+        self.heap.push(var);
+        self.sift_up(self.heap.len() - 1, activity);
     }
-}
-impl Ord for ScoreVar {
-    fn cmp(&self, other: &ScoreVar) -> Ordering {
-        self.phase.cmp(&other.phase)
+
+    /// Restores the heap order after the activity of `var` went up
+    fn increased(&mut self, var : LiteralSize, activity : &[f64]) {
+        if let Some(pos) = self.index[var] {
+            self.sift_up(pos, activity);
+        }
+    }
+
+    /// Restores the heap order after the activity of `var` changed either way
+    fn update(&mut self, var : LiteralSize, activity : &[f64]) {
+        if let Some(pos) = self.index[var] {
+            self.sift_up(pos, activity);
+            if let Some(pos) = self.index[var] {
+                self.sift_down(pos, activity);
+            }
+        }
+    }
+
+    fn top(&self) -> Option<LiteralSize> {
+        self.heap.first().copied()
+    }
+
+    /// Number of leading `decisions` with a higher activity than the best
+    /// unassigned variable. Assigned variables on top are dropped as in a pick.
+    fn reusable_levels(&mut self, decisions : &[LiteralSize], activity : &[f64], assig : &Assig) -> usize {
+        while let Some(var) = self.top() {
+            if assig.is_decidable(var) {
+                return decisions.iter().take_while(|&&dec| activity[dec] > activity[var]).count();
+            }
+            self.pop(activity);
+        }
+        decisions.len()
+    }
+
+    fn pop(&mut self, activity : &[f64]) -> Option<LiteralSize> {
+        let top = *self.heap.first()?;
+        let last = self.heap.len() - 1;
+        self.swap(0, last);
+        self.heap.pop();
+        self.index[top] = None;
+        if !self.heap.is_empty() {
+            self.sift_down(0, activity);
+        }
+        Some(top)
     }
 }
 
+/// Exponential VSIDS. Variables met during conflict analysis are bumped by an
+/// increment that grows by 1/decay after every conflict, which makes older bumps
+/// decay without touching every score. Unassigned variables are kept in a heap.
 #[derive(PartialEq,Debug)]
 pub struct VSIDS {
-    variable_scores : FxHashMap<LiteralSize,Phase>,
-    var_order : Vec<ScoreVar>,
-    nclause_counter : usize,
-    decay_rate : usize,
-    add_bump : usize,
-    
+    activity : Vec<f64>,
+    heap : VarHeap,
+    var_inc : f64,
+    decay : f64,
 }
 
-impl VSIDS {
-    pub fn sort_var_order(&mut self) {
-        for var in self.var_order.iter_mut() {
-            let act_phase = self.variable_scores.get(&var.var).unwrap();
-            var.phase.true_score = act_phase.true_score;
-            var.phase.false_score = act_phase.false_score;
+impl DecisionHeuristic for VSIDS {
+    fn add_var(&mut self, var : LiteralSize) {
+        self.activity.push(0.0);
+        self.heap.index.push(None);
+        self.heap.insert(var, &self.activity);
+    }
+
+    /// Pops variables until an unassigned one comes up. Assigned variables are put
+    /// back by `on_unassign` when they are backtracked.
+    fn pick(&mut self,assig : &Assig) -> Option<LiteralSize> {
+        while let Some(var) = self.heap.pop(&self.activity) {
+            if assig.is_decidable(var) {
+                return Some(var);
+            }
         }
-        self.var_order.sort_by(|a,b| b.partial_cmp(a).unwrap());
+        None
+    }
+
+    fn on_unassign(&mut self, var : LiteralSize, _assig : &Assig) {
+        self.heap.insert(var, &self.activity);
     }
-    fn decay(&mut self) {
-        for score in self.variable_scores.values_mut() {
-            score.true_score >>= 1;
-            score.false_score >>= 1;
+
+    fn on_conflict(&mut self, _learnt : &[Literal], analyzed : &mut [LiteralSize], _assig : &Assig) {
+        for &var in analyzed.iter() {
+            self.bump_var(var);
         }
+        self.decay();
     }
 
-    pub fn new(num_vars : usize) -> Self {
-        let mut var_ord  : Vec<ScoreVar> = Vec::with_capacity(num_vars);
-        
-        let mut var_scores: FxHashMap<LiteralSize,Phase> = FxHashMap::with_capacity_and_hasher(num_vars, Default::default());
+    /// Puts back every unassigned variable, the heap is not kept up to date while
+    /// another heuristic makes the decisions
+    fn reusable_levels(&mut self, decisions : &[LiteralSize], assig : &Assig) -> usize {
+        self.heap.reusable_levels(decisions, &self.activity, assig)
+    }
 
+    fn activate(&mut self, assig : &Assig) {
+        for var in 1..self.activity.len() {
+            if assig.is_decidable(var) {
+                self.heap.insert(var, &self.activity);
+            }
+        }
+    }
+}
+
+impl VSIDS {
+    pub fn new(num_vars : usize) -> Self {
+        let mut n = VSIDS{activity : vec![0.0; num_vars + 1],heap : VarHeap{heap : Vec::with_capacity(num_vars),index : vec![None; num_vars + 1]},var_inc : 1.0,decay : 0.95};
         for v in 1..=num_vars {
-           
-            var_scores.insert(v ,Default::default());
-        };
+            n.heap.insert(v, &n.activity);
+        }
+        n
+    }
+
+    pub fn activity(&self, var : LiteralSize) -> f64 {
+        self.activity[var]
+    }
+
+    pub fn bump_var(&mut self, var : LiteralSize) {
+        self.activity[var] += self.var_inc;
+        if self.activity[var] > ACTIVITY_LIMIT {
+            for act in self.activity.iter_mut() {
+                *act /= ACTIVITY_LIMIT;
+            }
+            self.var_inc /= ACTIVITY_LIMIT;
+        }
+        self.heap.increased(var, &self.activity);
+    }
+
+    /// Called once per conflict
+    pub fn decay(&mut self) {
+        self.var_inc /= self.decay;
+    }
+}
+
+/// Variable-move-to-front. All variables sit in a doubly linked queue ordered by
+/// the time they were last bumped, the most recent at the end. `search` points at
+/// a variable such that everything after it is assigned, so decisions walk
+/// backwards from there.
+#[derive(PartialEq,Debug)]
+pub struct VMTF {
+    prev : Vec<LiteralSize>,
+    next : Vec<LiteralSize>,
+    /// Enqueue time of each variable
+    stamp : Vec<u64>,
+    /// Variable 0 marks the ends of the queue
+    first : LiteralSize,
+    last : LiteralSize,
+    search : LiteralSize,
+    time : u64,
+}
+
+impl DecisionHeuristic for VMTF {
+    fn add_var(&mut self, var : LiteralSize) {
+        self.prev.push(0);
+        self.next.push(0);
+        self.stamp.push(0);
+        self.enqueue(var);
+        self.search = var;
+    }
+
+    fn pick(&mut self,assig : &Assig) -> Option<LiteralSize> {
+        let mut var = self.search;
+        while var != 0 && !assig.is_decidable(var) {
+            var = self.prev[var];
+        }
+        if var == 0 {
+            return None;
+        }
+        self.search = var;
+        Some(var)
+    }
+
+    fn on_unassign(&mut self, var : LiteralSize, _assig : &Assig) {
+        if self.stamp[var] > self.stamp[self.search] {
+            self.search = var;
+        }
+    }
+
+    fn on_conflict(&mut self, _learnt : &[Literal], analyzed : &mut [LiteralSize], assig : &Assig) {
+        self.bump_vars(analyzed, assig);
+    }
+
+    /// Decisions enqueued after the next variable to pick are kept
+    fn reusable_levels(&mut self, decisions : &[LiteralSize], assig : &Assig) -> usize {
+        match self.pick(assig) {
+            Some(next) => decisions.iter().take_while(|&&dec| self.stamp[dec] > self.stamp[next]).count(),
+            None => decisions.len(),
+        }
+    }
+
+    /// Points the search at the end of the queue
+    fn activate(&mut self, _assig : &Assig) {
+        self.search = self.last;
+    }
+}
+
+impl VMTF {
+    pub fn new(num_vars : usize) -> Self {
+        let mut n = VMTF{prev : vec![0; num_vars + 1],next : vec![0; num_vars + 1],stamp : vec![0; num_vars + 1],first : 0,last : 0,search : 0,time : 0};
         for v in 1..=num_vars {
-            let nvar =ScoreVar{phase : Default::default(),var : v as LiteralSize,stored_phase : None};
-            var_ord.push(nvar);
+            n.enqueue(v);
         }
-        
-        let mut n = VSIDS{variable_scores : var_scores,var_order : var_ord,nclause_counter : 0,decay_rate : 256,add_bump : 1} ;
-        n.sort_var_order();
+        n.search = n.last;
         n
+    }
 
+    fn enqueue(&mut self, var : LiteralSize) {
+        self.prev[var] = self.last;
+        self.next[var] = 0;
+        if self.last == 0 {
+            self.first = var;
+        } else {
+            self.next[self.last] = var;
+        }
+        self.last = var;
+        self.time += 1;
+        self.stamp[var] = self.time;
     }
 
-    pub fn pick_var(&mut self,assig : &Assig) -> Literal {
-        for var in self.var_order.iter_mut() {
-            if assig.get(&var.var).is_none() {
-                return literal_from_score_var(var);
+    fn dequeue(&mut self, var : LiteralSize) {
+        let (prev, next) = (self.prev[var], self.next[var]);
+        if prev == 0 {
+            self.first = next;
+        } else {
+            self.next[prev] = next;
+        }
+        if next == 0 {
+            self.last = prev;
+        } else {
+            self.prev[next] = prev;
+        }
+    }
+
+    pub fn stamp(&self, var : LiteralSize) -> u64 {
+        self.stamp[var]
+    }
+
+    /// Moves the variables to the end of the queue, keeping their relative order
+    pub fn bump_vars(&mut self, vars : &mut [LiteralSize], assig : &Assig) {
+        vars.sort_unstable_by_key(|&var| self.stamp[var]);
+        for &var in vars.iter() {
+            self.dequeue(var);
+            self.enqueue(var);
+            if assig.is_decidable(var) {
+                self.search = var;
             }
         }
-        unreachable!("No unassigned variables left");
     }
-  
+}
 
-    pub fn add_clause(&mut self, clause : &Clause) {
-        self.nclause_counter += 1;
-        if self.nclause_counter == self.decay_rate {
-            // println!("Decaying");
-            self.nclause_counter = 0;
-            self.decay();
-            self.sort_var_order();
+const ERWA_ALPHA_START : f64 = 0.4;
+const ERWA_ALPHA_MIN : f64 = 0.06;
+const ERWA_ALPHA_STEP : f64 = 1e-6;
+const LRB_LOCALITY_DECAY : f64 = 0.95;
+const CHB_REWARD_CONFLICT : f64 = 1.0;
+const CHB_REWARD_NO_CONFLICT : f64 = 0.9;
+
+/// Learning rate branching. While a variable is assigned it counts the conflicts
+/// it takes part in and the learned clauses whose reason side contains it; when it
+/// is unassigned both counts divided by the number of conflicts since it was
+/// assigned are the reward folded into its score with step size alpha. Scores of
+/// unassigned variables decay by 0.95 per conflict (locality), applied lazily when
+/// they reach the top of the heap.
+#[derive(PartialEq,Debug)]
+pub struct LRB {
+    activity : Vec<f64>,
+    heap : VarHeap,
+    alpha : f64,
+    participated : Vec<u64>,
+    reasoned : Vec<u64>,
+    /// Clock value up to which the locality decay has been applied
+    decayed_at : Vec<u64>,
+}
+
+impl DecisionHeuristic for LRB {
+    fn add_var(&mut self, var : LiteralSize) {
+        self.activity.push(0.0);
+        self.participated.push(0);
+        self.reasoned.push(0);
+        self.decayed_at.push(0);
+        self.heap.index.push(None);
+        self.heap.insert(var, &self.activity);
+    }
 
+    fn pick(&mut self,assig : &Assig) -> Option<LiteralSize> {
+        while let Some(var) = self.heap.top() {
+            if !assig.is_decidable(var) {
+                self.heap.pop(&self.activity);
+                continue;
+            }
+            let age = assig.time() - self.decayed_at[var];
+            if age == 0 {
+                return self.heap.pop(&self.activity);
+            }
+            self.activity[var] *= LRB_LOCALITY_DECAY.powi(age.min(i32::MAX as u64) as i32);
+            self.decayed_at[var] = assig.time();
+            self.heap.update(var, &self.activity);
         }
-        for lit in clause.literals.iter() {
-            let var = self.variable_scores.get_mut(&lit.var).unwrap();
-            var.bump(lit, self.add_bump);
+        None
+    }
 
+    fn on_assign(&mut self, var : LiteralSize, _assig : &Assig) {
+        self.participated[var] = 0;
+        self.reasoned[var] = 0;
+    }
+
+    fn on_unassign(&mut self, var : LiteralSize, assig : &Assig) {
+        let interval = assig.time() - assig.assigned_at(var);
+        if interval > 0 {
+            let reward = (self.participated[var] + self.reasoned[var]) as f64 / interval as f64;
+            self.activity[var] = (1.0 - self.alpha) * self.activity[var] + self.alpha * reward;
+            self.heap.update(var, &self.activity);
         }
+        self.decayed_at[var] = assig.time();
+        self.heap.insert(var, &self.activity);
     }
 
-    pub fn delete_clause(&mut self, clause : &Clause) {  
-        for lit in clause.literals.iter() {
-            let var = self.variable_scores.get_mut(&lit.var).unwrap();
-            var.decrement();
+    fn on_conflict(&mut self, _learnt : &[Literal], analyzed : &mut [LiteralSize], _assig : &Assig) {
+        for &var in analyzed.iter() {
+            self.participated[var] += 1;
         }
-       
+        self.alpha = (self.alpha - ERWA_ALPHA_STEP).max(ERWA_ALPHA_MIN);
+    }
+
+    fn wants_reason_side(&self) -> bool {
+        true
     }
+
+    fn on_reason_side(&mut self, vars : &[LiteralSize]) {
+        for &var in vars.iter() {
+            self.reasoned[var] += 1;
+        }
+    }
+
+    fn reusable_levels(&mut self, decisions : &[LiteralSize], assig : &Assig) -> usize {
+        self.heap.reusable_levels(decisions, &self.activity, assig)
+    }
+
+    fn activate(&mut self, assig : &Assig) {
+        for var in 1..self.activity.len() {
+            if assig.is_decidable(var) {
+                self.decayed_at[var] = assig.time();
+                self.heap.insert(var, &self.activity);
+            }
+        }
+    }
+}
+
+impl LRB {
+    pub fn new(num_vars : usize) -> Self {
+        let mut lrb = LRB{activity : vec![0.0], heap : VarHeap{heap : Vec::new(), index : vec![None]}, alpha : ERWA_ALPHA_START, participated : vec![0], reasoned : vec![0], decayed_at : vec![0]};
+        for var in 1..=num_vars {
+            lrb.add_var(var);
+        }
+        lrb
+    }
+
+    pub fn activity(&self, var : LiteralSize) -> f64 {
+        self.activity[var]
+    }
+}
+
+/// Conflict history based branching. Whenever a variable is assigned or takes part
+/// in a conflict its score moves towards a reward that is higher the more recently
+/// it was last seen in a conflict.
+#[derive(PartialEq,Debug)]
+pub struct CHB {
+    activity : Vec<f64>,
+    heap : VarHeap,
+    alpha : f64,
+    conflicts : u64,
+    last_conflict : Vec<u64>,
+}
+
+impl DecisionHeuristic for CHB {
+    fn add_var(&mut self, var : LiteralSize) {
+        self.activity.push(0.0);
+        self.last_conflict.push(0);
+        self.heap.index.push(None);
+        self.heap.insert(var, &self.activity);
+    }
+
+    fn pick(&mut self,assig : &Assig) -> Option<LiteralSize> {
+        while let Some(var) = self.heap.pop(&self.activity) {
+            if assig.is_decidable(var) {
+                return Some(var);
+            }
+        }
+        None
+    }
+
+    fn on_assign(&mut self, var : LiteralSize, _assig : &Assig) {
+        self.reward(var, CHB_REWARD_NO_CONFLICT);
+    }
+
+    fn on_unassign(&mut self, var : LiteralSize, _assig : &Assig) {
+        self.heap.insert(var, &self.activity);
+    }
+
+    fn on_conflict(&mut self, _learnt : &[Literal], analyzed : &mut [LiteralSize], _assig : &Assig) {
+        self.conflicts += 1;
+        for &var in analyzed.iter() {
+            self.last_conflict[var] = self.conflicts;
+            self.reward(var, CHB_REWARD_CONFLICT);
+        }
+        self.alpha = (self.alpha - ERWA_ALPHA_STEP).max(ERWA_ALPHA_MIN);
+    }
+
+    fn reusable_levels(&mut self, decisions : &[LiteralSize], assig : &Assig) -> usize {
+        self.heap.reusable_levels(decisions, &self.activity, assig)
+    }
+
+    fn activate(&mut self, assig : &Assig) {
+        for var in 1..self.activity.len() {
+            if assig.is_decidable(var) {
+                self.heap.insert(var, &self.activity);
+            }
+        }
+    }
+}
+
+impl CHB {
+    pub fn new(num_vars : usize) -> Self {
+        let mut chb = CHB{activity : vec![0.0], heap : VarHeap{heap : Vec::new(), index : vec![None]}, alpha : ERWA_ALPHA_START, conflicts : 0, last_conflict : vec![0]};
+        for var in 1..=num_vars {
+            chb.add_var(var);
+        }
+        chb
+    }
+
+    pub fn activity(&self, var : LiteralSize) -> f64 {
+        self.activity[var]
+    }
+
+    fn reward(&mut self, var : LiteralSize, multiplier : f64) {
+        let reward = multiplier / (self.conflicts - self.last_conflict[var] + 1) as f64;
+        self.activity[var] = (1.0 - self.alpha) * self.activity[var] + self.alpha * reward;
+        self.heap.update(var, &self.activity);
+    }
+}
+
+/// Which heuristic makes the decisions. `Switching` uses VMTF in focused mode and
+/// VSIDS in stable mode.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Default)]
+pub enum HeuristicKind {
+    #[default]
+    VSIDS,
+    VMTF,
+    Switching,
+    LRB,
+    CHB,
+}
+
+impl HeuristicKind {
+    /// Names accepted by `--heuristic`
+    pub const NAMES : &'static [&'static str] = &["vsids", "vmtf", "switch", "lrb", "chb"];
+
+    pub fn from_name(name : &str) -> Option<Self> {
+        match name {
+            "vsids" => Some(HeuristicKind::VSIDS),
+            "vmtf" => Some(HeuristicKind::VMTF),
+            "switch" => Some(HeuristicKind::Switching),
+            "lrb" => Some(HeuristicKind::LRB),
+            "chb" => Some(HeuristicKind::CHB),
+            _ => None,
+        }
+    }
+
+    /// The heuristics for focused mode and, if it differs, for stable mode
+    fn build(self, num_vars : usize) -> (Box<dyn DecisionHeuristic>, Option<Box<dyn DecisionHeuristic>>) {
+        match self {
+            HeuristicKind::VSIDS => (Box::new(VSIDS::new(num_vars)), None),
+            HeuristicKind::VMTF => (Box::new(VMTF::new(num_vars)), None),
+            HeuristicKind::Switching => (Box::new(VMTF::new(num_vars)), Some(Box::new(VSIDS::new(num_vars)))),
+            HeuristicKind::LRB => (Box::new(LRB::new(num_vars)), None),
+            HeuristicKind::CHB => (Box::new(CHB::new(num_vars)), None),
+        }
+    }
+}
+
+/// The decision heuristics in use together with the polarity of each decision
+#[derive(Debug)]
+pub struct Heuristic {
+    kind : HeuristicKind,
+    stable : bool,
+    focused_heuristic : Box<dyn DecisionHeuristic>,
+    stable_heuristic : Option<Box<dyn DecisionHeuristic>>,
+    num_vars : usize,
+    /// Occurrences of both literals of each variable in the original clauses, they
+    /// decide the polarity of the first decision on a variable
+    occurrences : Vec<Phase>,
+    phases : Phases,
 }
 
+impl Heuristic {
+    pub fn new(num_vars : usize) -> Self {
+        let (focused_heuristic, stable_heuristic) = HeuristicKind::default().build(num_vars);
+        Heuristic{kind : HeuristicKind::default(),stable : false,focused_heuristic,stable_heuristic,num_vars,occurrences : vec![Default::default(); num_vars + 1],phases : Phases::new(num_vars)}
+    }
+
+    fn active(&mut self) -> &mut dyn DecisionHeuristic {
+        match (&mut self.stable_heuristic, self.stable) {
+            (Some(heuristic), true) => heuristic.as_mut(),
+            _ => self.focused_heuristic.as_mut(),
+        }
+    }
+
+    /// Replaces the heuristics, the new ones start from scratch
+    pub fn set_kind(&mut self, kind : HeuristicKind, assig : &Assig) {
+        if kind == self.kind {
+            return;
+        }
+        let (focused_heuristic, stable_heuristic) = kind.build(self.num_vars);
+        self.kind = kind;
+        self.focused_heuristic = focused_heuristic;
+        self.stable_heuristic = stable_heuristic;
+        self.active().activate(assig);
+    }
+
+    pub fn kind(&self) -> HeuristicKind {
+        self.kind
+    }
+
+    /// Tells the heuristic that the solver moved between stable and focused mode
+    pub fn set_stable(&mut self, stable : bool, assig : &Assig) {
+        if stable == self.stable {
+            return;
+        }
+        self.stable = stable;
+        if self.stable_heuristic.is_some() {
+            self.active().activate(assig);
+        }
+    }
+
+    pub fn add_var(&mut self, var : LiteralSize) {
+        self.num_vars += 1;
+        self.focused_heuristic.add_var(var);
+        if let Some(heuristic) = self.stable_heuristic.as_mut() {
+            heuristic.add_var(var);
+        }
+        self.occurrences.push(Default::default());
+        self.phases.add_var();
+    }
+
+    /// The polarity a variable gets before it has been assigned: the more
+    /// frequent one in the original clauses
+    fn original_phase(occurrences : &[Phase], var : LiteralSize) -> bool {
+        occurrences[var].true_score > occurrences[var].false_score
+    }
+
+    fn literal_with_phase(&mut self, var : LiteralSize) -> Literal {
+        let sign = self.phases.decide(var, self.stable).unwrap_or_else(|| Self::original_phase(&self.occurrences, var));
+        Literal::new(var, sign)
+    }
+
+    pub fn saved_phase(&self, var : LiteralSize) -> bool {
+        self.phases.saved(var).unwrap_or_else(|| Self::original_phase(&self.occurrences, var))
+    }
+
+    pub fn set_saved_phase(&mut self, var : LiteralSize, sign : bool) {
+        self.phases.set_saved(var, sign);
+    }
+
+    /// Records a conflict-free prefix of the trail for the target and best phases
+    pub fn update_phases(&mut self, trail : &[Decision]) {
+        self.phases.update(trail);
+    }
+
+    pub fn rephase(&mut self, kind : Rephase, rng : &mut Rng) {
+        let occurrences = &self.occurrences;
+        self.phases.rephase(kind, |var| Self::original_phase(occurrences, var), rng);
+    }
+
+    pub fn pick_var(&mut self,assig : &Assig) -> Literal {
+        match self.active().pick(assig) {
+            Some(var) => self.literal_with_phase(var),
+            None => unreachable!("No unassigned variables left"),
+        }
+    }
+
+    pub fn assign_var(&mut self, var : LiteralSize, assig : &Assig) {
+        self.active().on_assign(var, assig);
+    }
+
+    /// Saves the value of `lit` as the phase of its variable
+    pub fn unassign(&mut self, lit : Literal, assig : &Assig) {
+        self.phases.save(lit);
+        self.active().on_unassign(lit.var(), assig);
+    }
+
+    /// Reports one analyzed conflict
+    pub fn conflict(&mut self, learnt : &[Literal], analyzed : &mut [LiteralSize], assig : &Assig) {
+        self.active().on_conflict(learnt, analyzed, assig);
+    }
+
+    pub fn wants_reason_side(&mut self) -> bool {
+        self.active().wants_reason_side()
+    }
+
+    pub fn reason_side(&mut self, vars : &[LiteralSize]) {
+        self.active().on_reason_side(vars);
+    }
+
+    pub fn learned_clause(&mut self, clause : &[Literal]) {
+        self.active().on_learned_clause(clause);
+    }
+
+    pub fn reusable_levels(&mut self, decisions : &[LiteralSize], assig : &Assig) -> usize {
+        self.active().reusable_levels(decisions, assig)
+    }
+
+    pub fn restart(&mut self) {
+        self.phases.reset_target();
+        self.active().on_restart();
+    }
+
+    /// Counts the literals of an original clause for the initial polarities
+    pub fn add_clause(&mut self, clause : &[Literal]) {
+        for lit in clause.iter() {
+            self.occurrences[lit.var()].bump(lit, 1);
+        }
+    }
+
+    /// Starts over after the clause database was rewritten: the occurrences are
+    /// recounted from `clauses` and every decidable variable is put back in the
+    /// queue of the active heuristic, variables that were eliminated or fixed
+    /// meanwhile are skipped by the next picks
+    pub fn rebuild<'a>(&mut self, clauses : impl Iterator<Item = &'a [Literal]>, assig : &Assig) {
+        self.occurrences.iter_mut().for_each(|occurrences| *occurrences = Default::default());
+        for clause in clauses {
+            self.add_clause(clause);
+        }
+        self.active().activate(assig);
+    }
+}
+
+#[cfg(test)]
+mod tests;
+
 // pub fn jsw(&self) -> Vec<Literal> {
 //     let mut jsw_vec = vec![[0 as f32, 0 as f32]; self.num_variables + 1];
 //     for clause in self.clauses.iter() {
-//         for lit in clause.literals.iter() {
+//         for lit in clause.iter() {
 //             let qnt = 2.0_f32.powf(-1.0 * (clause.literals.len() as f32));
 //             if lit.is_negative() {
 //                 jsw_vec[lit.var][0] += qnt;
@@ -161,4 +779,4 @@ impl VSIDS {
 //         .iter()
 //         .filter_map(|&k| if k.1.var != 0 { Some(k.1) } else { None })
 //         .collect();
-// }
\ No newline at end of file
+// }
@@ -0,0 +1,194 @@
+use super::*;
+
+#[test]
+fn vsids_pick_var_follows_activity() {
+    let mut h = VSIDS::new(4);
+    let assig = Assig::new(4);
+    h.bump_var(3);
+    h.decay();
+    h.bump_var(2);
+    h.bump_var(2);
+    assert_eq!(h.pick(&assig), Some(2));
+    assert_eq!(h.pick(&assig), Some(3));
+}
+
+#[test]
+fn vsids_bump_increment_grows_after_decay() {
+    let mut h = VSIDS::new(2);
+    h.bump_var(1);
+    h.decay();
+    h.bump_var(2);
+    assert!(h.activity(2) > h.activity(1));
+}
+
+#[test]
+fn vsids_activity_rescales_on_overflow() {
+    let mut h = VSIDS::new(2);
+    h.var_inc = 1e99;
+    h.bump_var(1);
+    h.bump_var(1);
+    assert!(h.activity(1) <= ACTIVITY_LIMIT);
+    h.bump_var(2);
+    assert!(h.activity(2) < h.activity(1));
+}
+
+#[test]
+fn vsids_assigned_vars_are_skipped_and_reinserted() {
+    let mut h = VSIDS::new(3);
+    let mut assig = Assig::new(3);
+    h.bump_var(1);
+    h.bump_var(1);
+    h.bump_var(2);
+    assig.insert(1, AssigInfo::new(true, 1));
+    assert_eq!(h.pick(&assig), Some(2));
+    assig.remove(&1);
+    h.on_unassign(1, &assig);
+    h.on_unassign(2, &assig);
+    assert_eq!(h.pick(&assig), Some(1));
+}
+
+#[test]
+fn vmtf_picks_most_recently_bumped() {
+    let mut h = VMTF::new(4);
+    let mut assig = Assig::new(4);
+    // the last enqueued variable comes first
+    assert_eq!(h.pick(&assig), Some(4));
+    assig.insert(1, AssigInfo::new(true, 1));
+    assig.insert(3, AssigInfo::new(true, 1));
+    h.bump_vars(&mut [3, 1], &assig);
+    // 1 was enqueued before 3, so the bump keeps that order
+    assert!(h.stamp(3) > h.stamp(1));
+    assert_eq!(h.pick(&assig), Some(4));
+    assig.insert(4, AssigInfo::new(true, 1));
+    assert_eq!(h.pick(&assig), Some(2));
+
+    assig.remove(&3);
+    h.on_unassign(3, &assig);
+    assert_eq!(h.pick(&assig), Some(3));
+}
+
+#[test]
+fn vmtf_search_skips_assigned() {
+    let mut h = VMTF::new(3);
+    let mut assig = Assig::new(3);
+    for var in 1..=3 {
+        assig.insert(var, AssigInfo::new(false, 1));
+    }
+    assert_eq!(h.pick(&assig), None);
+    assig.remove(&2);
+    h.on_unassign(2, &assig);
+    assert_eq!(h.pick(&assig), Some(2));
+}
+
+#[test]
+fn switching_uses_vsids_when_stable() {
+    let mut h = Heuristic::new(3);
+    let assig = Assig::new(3);
+    h.set_kind(HeuristicKind::Switching, &assig);
+    // vmtf in focused mode prefers the last variable
+    assert_eq!(h.pick_var(&assig).var(), 3);
+    h.set_stable(true, &assig);
+    h.conflict(&[], &mut [1], &assig);
+    assert_eq!(h.pick_var(&assig).var(), 1);
+}
+
+#[test]
+fn first_phase_follows_occurrences() {
+    let mut h = Heuristic::new(1);
+    h.add_clause(&[Literal::from(-1)]);
+    h.add_clause(&[Literal::from(-1)]);
+    h.add_clause(&[Literal::from(1)]);
+    assert_eq!(h.pick_var(&Assig::new(1)), Literal::from(-1));
+}
+
+#[test]
+fn heuristic_names_round_trip() {
+    for name in HeuristicKind::NAMES {
+        assert!(HeuristicKind::from_name(name).is_some());
+    }
+    assert_eq!(HeuristicKind::from_name("vmtf"), Some(HeuristicKind::VMTF));
+    assert_eq!(HeuristicKind::from_name("random"), None);
+}
+
+#[test]
+fn vsids_conflict_hook_bumps_analyzed_vars() {
+    let mut h: Box<dyn DecisionHeuristic> = Box::new(VSIDS::new(3));
+    let assig = Assig::new(3);
+    h.on_conflict(&[Literal::from(-2)], &mut [2, 3], &assig);
+    h.on_conflict(&[Literal::from(2)], &mut [2], &assig);
+    assert_eq!(h.pick(&assig), Some(2));
+    assert_eq!(h.pick(&assig), Some(3));
+}
+
+#[test]
+fn lrb_rewards_participation_rate() {
+    let mut h = LRB::new(3);
+    let mut assig = Assig::new(3);
+    for var in 1..=3 {
+        assig.insert(var, AssigInfo::new(true, 1));
+        h.on_assign(var, &assig);
+    }
+    // 1 takes part in both conflicts, 2 in one and 3 only in a reason side
+    h.on_conflict(&[], &mut [1, 2], &assig);
+    assig.tick();
+    h.on_conflict(&[], &mut [1], &assig);
+    h.on_reason_side(&[3]);
+    assig.tick();
+    for var in 1..=3 {
+        assig.remove(&var);
+        h.on_unassign(var, &assig);
+    }
+    assert!(h.activity(1) > h.activity(2));
+    assert_eq!(h.activity(2), h.activity(3));
+    assert_eq!(h.pick(&assig), Some(1));
+}
+
+#[test]
+fn lrb_locality_decays_unassigned_scores() {
+    let mut h = LRB::new(2);
+    let mut assig = Assig::new(2);
+    assig.insert(1, AssigInfo::new(true, 1));
+    h.on_assign(1, &assig);
+    h.on_conflict(&[], &mut [1], &assig);
+    assig.tick();
+    assig.remove(&1);
+    h.on_unassign(1, &assig);
+    let score = h.activity(1);
+    assig.tick();
+    assig.tick();
+    assert_eq!(h.pick(&assig), Some(1));
+    assert!((h.activity(1) - score * 0.95 * 0.95).abs() < 1e-12);
+}
+
+#[test]
+fn chb_prefers_recent_conflict_vars() {
+    let mut h = CHB::new(3);
+    let assig = Assig::new(3);
+    h.on_conflict(&[], &mut [2], &assig);
+    h.on_conflict(&[], &mut [3], &assig);
+    h.on_conflict(&[], &mut [], &assig);
+    // assigning 2 long after its conflict pulls its score down, 1 was never in one
+    h.on_assign(2, &assig);
+    h.on_assign(1, &assig);
+    assert!(h.activity(3) > h.activity(2));
+    assert!(h.activity(2) > h.activity(1));
+    assert!(h.activity(1) > 0.0);
+    assert_eq!(h.pick(&assig), Some(3));
+}
+
+#[test]
+fn reusable_levels_keep_preferred_decisions() {
+    let mut h = VSIDS::new(4);
+    let mut assig = Assig::new(4);
+    for (var, bumps) in [(1, 3), (2, 1), (3, 2)] {
+        for _ in 0..bumps {
+            h.bump_var(var);
+        }
+    }
+    for var in [1, 2] {
+        assig.insert(var, AssigInfo::new(true, var));
+    }
+    // 3 would be picked next, it beats the decision on 2 but not the one on 1
+    assert_eq!(h.reusable_levels(&[1, 2], &assig), 1);
+    assert_eq!(h.reusable_levels(&[2, 1], &assig), 0);
+}
@@ -0,0 +1,217 @@
+use crate::ds::*;
+
+/// Effort every pass gets during `preprocess`, in ticks
+pub const PREPROCESS_EFFORT: usize = 20_000_000;
+/// A pass that is due never gets less than this
+const MIN_EFFORT: usize = 100_000;
+
+/// The simplification passes run during search
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InprocessPass {
+    Probe,
+    Substitute,
+    Subsume,
+    Vivify,
+    Eliminate,
+}
+
+impl InprocessPass {
+    /// Every pass, in the order they run when several are due
+    pub const ALL: [InprocessPass; 5] = [
+        InprocessPass::Probe,
+        InprocessPass::Substitute,
+        InprocessPass::Subsume,
+        InprocessPass::Vivify,
+        InprocessPass::Eliminate,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            InprocessPass::Probe => "probe",
+            InprocessPass::Substitute => "substitute",
+            InprocessPass::Subsume => "subsume",
+            InprocessPass::Vivify => "vivify",
+            InprocessPass::Eliminate => "eliminate",
+        }
+    }
+
+    /// Passes that remove variables, the models of those have to be rebuilt
+    pub fn eliminates(self) -> bool {
+        matches!(self, InprocessPass::Substitute | InprocessPass::Eliminate)
+    }
+
+    /// Conflicts before the first run
+    fn first_interval(self) -> usize {
+        match self {
+            InprocessPass::Probe => 3000,
+            InprocessPass::Substitute => 3000,
+            InprocessPass::Subsume => 5000,
+            InprocessPass::Vivify => 4000,
+            InprocessPass::Eliminate => 10000,
+        }
+    }
+
+    /// Share of the propagation ticks of the search since the last run the pass
+    /// may spend, in permille
+    fn relative_effort(self) -> usize {
+        match self {
+            InprocessPass::Probe => 100,
+            InprocessPass::Substitute => 0,
+            InprocessPass::Subsume => 100,
+            InprocessPass::Vivify => 150,
+            InprocessPass::Eliminate => 100,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct PassSchedule {
+    pass: InprocessPass,
+    /// Conflict count from which on the pass is due
+    next: usize,
+    interval: usize,
+    runs: usize,
+    /// Propagation ticks when the pass last ran
+    last_ticks: usize,
+}
+
+/// When each simplification pass runs next. Intervals grow geometrically, every
+/// run multiplies the interval of its pass by 3/2.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Inprocessing {
+    schedules: Vec<PassSchedule>,
+    /// Whether passes that eliminate variables may run, not when every model of
+    /// the formula has to be kept
+    pub allow_elimination: bool,
+}
+
+impl Default for Inprocessing {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Inprocessing {
+    pub fn new() -> Self {
+        Inprocessing {
+            schedules: InprocessPass::ALL
+                .iter()
+                .map(|&pass| PassSchedule {
+                    pass,
+                    next: pass.first_interval(),
+                    interval: pass.first_interval(),
+                    runs: 0,
+                    last_ticks: 0,
+                })
+                .collect(),
+            allow_elimination: false,
+        }
+    }
+
+    /// The passes due after `conflicts` conflicts, in the order they run
+    pub fn due(&self, conflicts: usize) -> Vec<InprocessPass> {
+        self.schedules
+            .iter()
+            .filter(|schedule| schedule.next <= conflicts)
+            .filter(|schedule| self.allow_elimination || !schedule.pass.eliminates())
+            .map(|schedule| schedule.pass)
+            .collect()
+    }
+
+    /// Records a run of `pass` and returns its effort in ticks: its share of the
+    /// propagation ticks since its last run
+    pub fn start(&mut self, pass: InprocessPass, conflicts: usize, ticks: usize) -> usize {
+        let schedule = self
+            .schedules
+            .iter_mut()
+            .find(|schedule| schedule.pass == pass)
+            .unwrap();
+        let effort = (ticks - schedule.last_ticks) * pass.relative_effort() / 1000;
+        schedule.runs += 1;
+        schedule.last_ticks = ticks;
+        schedule.interval += schedule.interval / 2;
+        schedule.next = conflicts + schedule.interval;
+        effort.max(MIN_EFFORT)
+    }
+
+    /// How often each pass ran
+    pub fn runs(&self) -> impl Iterator<Item = (InprocessPass, usize)> + '_ {
+        self.schedules
+            .iter()
+            .map(|schedule| (schedule.pass, schedule.runs))
+    }
+}
+
+impl SolverState {
+    /// Runs the simplification passes that are due, after a restart. Levels kept
+    /// by trail reuse are undone first, the passes work at level 0. They share
+    /// the reconstruction stack with preprocessing.
+    pub fn inprocess_if_needed(&mut self) {
+        let due = self.inprocessing.due(self.conflicts);
+        if due.is_empty() || self.unsat_at_root {
+            return;
+        }
+        self.backtrack_to_root();
+        for pass in due {
+            if self.unsat_at_root {
+                return;
+            }
+            let effort = self
+                .inprocessing
+                .start(pass, self.conflicts, self.propagation_ticks);
+            match pass {
+                InprocessPass::Probe => self.probe(effort),
+                InprocessPass::Substitute => {
+                    self.simplify_stats.substituted += self.substitute_equivalences()
+                }
+                InprocessPass::Subsume => self.subsume(true, effort),
+                InprocessPass::Vivify => {
+                    self.vivify(effort);
+                }
+                InprocessPass::Eliminate => {
+                    self.simplify_stats.eliminated += self.eliminate_variables(effort)
+                }
+            }
+        }
+    }
+
+    /// Brings the state back in shape after a simplification pass that edited the
+    /// clause database directly: rebuilds what depends on it and asserts the
+    /// units the pass found.
+    pub(super) fn finish_simplification(&mut self, units: &[Literal]) {
+        debug_assert!(self.level == 0);
+        self.rebuild_after_rewrite();
+        for &unit in units.iter() {
+            if literal_falsified(&unit, &self.assig) {
+                self.unsat_at_root = true;
+            } else if literal_unassigned(&unit, &self.assig) {
+                self.add_decision(&Decision::make_assertunit(unit));
+            }
+        }
+        if !self.unsat_at_root {
+            if let FormulaUnitProp::Conflict { .. } = self.unit_prop() {
+                self.unsat_at_root = true;
+            }
+        }
+    }
+
+    /// Frees deleted clauses, watches the remaining ones again and lets the
+    /// heuristic recount literal occurrences and drop variables that are no
+    /// longer decidable
+    pub fn rebuild_after_rewrite(&mut self) {
+        assert!(self.level == 0);
+        self.collect_garbage();
+        self.reset_watchlist();
+        let clause_db = &self.clause_db;
+        self.decision_heuristic.rebuild(
+            clause_db
+                .iter()
+                .filter(|&cref| !clause_db.is_learned(cref))
+                .map(|cref| clause_db.lits(cref)),
+            &self.assig,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,97 @@
+use super::*;
+use crate::test_util::{assert_reconstructs, clauses, state};
+
+fn with_elimination() -> Inprocessing {
+    let mut inprocessing = Inprocessing::new();
+    inprocessing.allow_elimination = true;
+    inprocessing
+}
+
+#[test]
+fn passes_are_due_after_their_first_interval() {
+    let inprocessing = with_elimination();
+    assert!(inprocessing.due(0).is_empty());
+    assert_eq!(
+        inprocessing.due(3000),
+        vec![InprocessPass::Probe, InprocessPass::Substitute]
+    );
+    assert_eq!(inprocessing.due(10000), InprocessPass::ALL.to_vec());
+}
+
+#[test]
+fn eliminating_passes_need_permission() {
+    let inprocessing = Inprocessing::new();
+    assert_eq!(
+        inprocessing.due(10000),
+        vec![
+            InprocessPass::Probe,
+            InprocessPass::Subsume,
+            InprocessPass::Vivify
+        ]
+    );
+}
+
+#[test]
+fn intervals_grow_geometrically() {
+    let mut inprocessing = with_elimination();
+    inprocessing.start(InprocessPass::Probe, 3000, 0);
+    // 3000 + 3000 * 3 / 2
+    assert!(!inprocessing.due(7499).contains(&InprocessPass::Probe));
+    assert!(inprocessing.due(7500).contains(&InprocessPass::Probe));
+    inprocessing.start(InprocessPass::Probe, 7500, 0);
+    // 7500 + 4500 * 3 / 2
+    assert!(!inprocessing.due(14249).contains(&InprocessPass::Probe));
+    assert!(inprocessing.due(14250).contains(&InprocessPass::Probe));
+    // the other passes keep their schedule
+    assert!(inprocessing.due(3000).contains(&InprocessPass::Substitute));
+    let runs: Vec<(InprocessPass, usize)> = inprocessing.runs().collect();
+    assert_eq!(runs[0], (InprocessPass::Probe, 2));
+    assert!(runs[1..].iter().all(|&(_, runs)| runs == 0));
+}
+
+#[test]
+fn effort_is_share_of_ticks_since_last_run() {
+    let mut inprocessing = with_elimination();
+    assert_eq!(
+        inprocessing.start(InprocessPass::Vivify, 4000, 10_000_000),
+        1_500_000
+    );
+    assert_eq!(
+        inprocessing.start(InprocessPass::Vivify, 10000, 14_000_000),
+        600_000
+    );
+    assert_eq!(
+        inprocessing.start(InprocessPass::Vivify, 20000, 14_000_000),
+        MIN_EFFORT
+    );
+    assert_eq!(
+        inprocessing.start(InprocessPass::Substitute, 3000, 50_000_000),
+        MIN_EFFORT
+    );
+}
+
+#[test]
+fn due_passes_run_and_keep_models() {
+    // 1 = 2 through the binaries, 6 only occurs positively
+    let original = clauses(&[
+        &[-1, 2],
+        &[1, -2],
+        &[1, 3, 4],
+        &[1, 3, 4, 6],
+        &[-3, 5],
+        &[-4, 5],
+        &[-5, 6, 7],
+        &[2, -7, 8],
+        &[-8, -3, -1],
+        &[3, -8, 4],
+    ]);
+    let mut s = state(8, &original);
+    s.inprocessing.allow_elimination = true;
+    s.conflicts = 10000;
+    s.inprocess_if_needed();
+    assert!(s.inprocessing.runs().all(|(_, runs)| runs == 1));
+    assert!(s.inprocessing.due(10000).is_empty());
+    assert!(s.check_watch_invariant());
+    assert!(s.simplify_stats.substituted >= 1);
+    assert_reconstructs(&mut s, &original);
+}
@@ -0,0 +1,62 @@
+use crate::ds::heuristic::HeuristicKind;
+use crate::ds::restart::RestartPolicy;
+
+/// Search settings that can be changed from the command line
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SolverOptions {
+    /// Backtrack chronologically, one level at a time, when a backjump would undo
+    /// more than this many levels. None always backjumps.
+    pub chrono_threshold: Option<usize>,
+    pub heuristic: HeuristicKind,
+    pub restart: RestartPolicy,
+    /// Keep the decision levels a restart would rebuild the same way
+    pub reuse_trail: bool,
+}
+
+/// Jump distance above which chronological backtracking kicks in by default
+pub const DEFAULT_CHRONO_THRESHOLD: usize = 100;
+
+impl SolverOptions {
+    /// Reads the settings from command line arguments, None if one is unknown
+    /// or lacks its value. `--restart mode` also switches the heuristic with
+    /// the mode, VSIDS in stable and VMTF in focused phases, unless
+    /// `--heuristic` picks one.
+    pub fn from_args(args: &[String]) -> Option<Self> {
+        let mut options = SolverOptions::default();
+        let mut heuristic_given = false;
+        let mut idx = 0;
+        while idx < args.len() {
+            match args[idx].as_str() {
+                "--chrono" => {
+                    // the threshold is optional
+                    match args.get(idx + 1).and_then(|n| n.parse().ok()) {
+                        Some(threshold) => {
+                            options.chrono_threshold = Some(threshold);
+                            idx += 1;
+                        }
+                        None => options.chrono_threshold = Some(DEFAULT_CHRONO_THRESHOLD),
+                    }
+                }
+                "--heuristic" => {
+                    options.heuristic = HeuristicKind::from_name(args.get(idx + 1)?)?;
+                    heuristic_given = true;
+                    idx += 1;
+                }
+                "--restart" => {
+                    options.restart = RestartPolicy::from_name(args.get(idx + 1)?)?;
+                    idx += 1;
+                }
+                "--reuse-trail" => options.reuse_trail = true,
+                _ => return None,
+            }
+            idx += 1;
+        }
+        if options.restart == RestartPolicy::Mode && !heuristic_given {
+            options.heuristic = HeuristicKind::Switching;
+        }
+        Some(options)
+    }
+}
+
+#[cfg(test)]
+mod tests;
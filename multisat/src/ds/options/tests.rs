@@ -0,0 +1,37 @@
+use super::*;
+
+fn parse(args: &[&str]) -> Option<SolverOptions> {
+    let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+    SolverOptions::from_args(&args)
+}
+
+#[test]
+fn options_are_parsed() {
+    assert_eq!(parse(&[]), Some(SolverOptions::default()));
+    let options = parse(&["--chrono", "--heuristic", "lrb", "--reuse-trail"]).unwrap();
+    assert_eq!(options.chrono_threshold, Some(DEFAULT_CHRONO_THRESHOLD));
+    assert_eq!(options.heuristic, HeuristicKind::LRB);
+    assert!(options.reuse_trail);
+    assert_eq!(parse(&["--chrono", "5"]).unwrap().chrono_threshold, Some(5));
+    assert_eq!(parse(&["--heuristic"]), None);
+    assert_eq!(parse(&["--restart", "often"]), None);
+    assert_eq!(parse(&["--fast"]), None);
+}
+
+#[test]
+fn mode_restarts_switch_the_heuristic_too() {
+    let options = parse(&["--restart", "mode"]).unwrap();
+    assert_eq!(options.restart, RestartPolicy::Mode);
+    assert_eq!(options.heuristic, HeuristicKind::Switching);
+    // an explicit heuristic wins, in either order
+    for args in [
+        ["--restart", "mode", "--heuristic", "vsids"],
+        ["--heuristic", "vsids", "--restart", "mode"],
+    ] {
+        assert_eq!(parse(&args).unwrap().heuristic, HeuristicKind::VSIDS);
+    }
+    assert_eq!(
+        parse(&["--restart", "luby"]).unwrap().heuristic,
+        HeuristicKind::default()
+    );
+}
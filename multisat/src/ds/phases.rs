@@ -0,0 +1,130 @@
+use crate::ds::utils::*;
+
+/// Ways of resetting the saved phases, applied in turn every few thousand
+/// conflicts so the search does not stay stuck around one assignment
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rephase {
+    /// The initial polarities
+    Original,
+    /// The opposite of the initial polarities
+    Inverted,
+    /// The opposite of the saved phases
+    Flipped,
+    Random,
+    /// The phases of the longest conflict-free trail since the last rephase
+    Best,
+    /// The best assignment found by local search from the saved phases
+    Walk,
+}
+
+/// Order of the rephases, starting over at the end
+pub const REPHASE_CYCLE: &[Rephase] = &[
+    Rephase::Original,
+    Rephase::Best,
+    Rephase::Walk,
+    Rephase::Inverted,
+    Rephase::Best,
+    Rephase::Walk,
+    Rephase::Flipped,
+    Rephase::Best,
+    Rephase::Walk,
+    Rephase::Random,
+];
+
+/// The polarities a decision can take. The saved phase is the last value of a
+/// variable, the target and best phases are copies of the longest trails that
+/// reached no conflict, since the last restart and the last rephase respectively.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Phases {
+    saved: Vec<Option<bool>>,
+    target: Vec<Option<bool>>,
+    best: Vec<Option<bool>>,
+    target_len: usize,
+    best_len: usize,
+}
+
+impl Phases {
+    pub fn new(num_vars: usize) -> Self {
+        Phases {
+            saved: vec![None; num_vars + 1],
+            target: vec![None; num_vars + 1],
+            best: vec![None; num_vars + 1],
+            target_len: 0,
+            best_len: 0,
+        }
+    }
+
+    pub fn add_var(&mut self) {
+        self.saved.push(None);
+        self.target.push(None);
+        self.best.push(None);
+    }
+
+    /// Remembers the value of a literal that is being unassigned
+    pub fn save(&mut self, lit: Literal) {
+        self.saved[lit.var()] = Some(lit.sign());
+    }
+
+    pub fn saved(&self, var: LiteralSize) -> Option<bool> {
+        self.saved[var]
+    }
+
+    pub fn set_saved(&mut self, var: LiteralSize, sign: bool) {
+        self.saved[var] = Some(sign);
+    }
+
+    /// Polarity of the next decision on `var`. Stable mode steers towards the
+    /// target assignment, focused mode only uses the saved phases.
+    pub fn decide(&self, var: LiteralSize, stable: bool) -> Option<bool> {
+        if stable {
+            self.target[var].or(self.saved[var])
+        } else {
+            self.saved[var]
+        }
+    }
+
+    /// Records `trail`, a prefix of the trail that propagated without conflict,
+    /// as target and best phases if it is the longest seen so far. Shorter
+    /// prefixes, which most conflicts give, cost nothing.
+    pub fn update(&mut self, trail: &[Decision]) {
+        if trail.len() > self.target_len {
+            self.target_len = trail.len();
+            for lit in trail.iter().map(Decision::get_lit) {
+                self.target[lit.var()] = Some(lit.sign());
+            }
+        }
+        if trail.len() > self.best_len {
+            self.best_len = trail.len();
+            for lit in trail.iter().map(Decision::get_lit) {
+                self.best[lit.var()] = Some(lit.sign());
+            }
+        }
+    }
+
+    pub fn reset_target(&mut self) {
+        self.target_len = 0;
+    }
+
+    /// Overwrites the saved phases, `original` gives the initial polarity of a
+    /// variable. Walk phases come from local search and are set with `set_saved`.
+    pub fn rephase(&mut self, kind: Rephase, original: impl Fn(LiteralSize) -> bool, rng: &mut Rng) {
+        for var in 1..self.saved.len() {
+            let phase = match kind {
+                Rephase::Original => Some(original(var)),
+                Rephase::Inverted => Some(!original(var)),
+                Rephase::Flipped => self.saved[var].map(|sign| !sign),
+                Rephase::Random => Some(rng.coin()),
+                Rephase::Best => self.best[var].or(self.saved[var]),
+                Rephase::Walk => self.saved[var],
+            };
+            self.saved[var] = phase;
+        }
+        if kind == Rephase::Best {
+            self.best_len = 0;
+        }
+        self.target_len = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,63 @@
+use super::*;
+use crate::test_util::lits;
+
+/// A trail of decisions on `lits`
+fn trail(dimacs: &[i32]) -> Vec<Decision> {
+    lits(dimacs).into_iter().map(|lit| Decision::Choice { lit }).collect()
+}
+
+#[test]
+fn saved_phase_follows_last_value() {
+    let mut phases = Phases::new(2);
+    assert_eq!(phases.decide(1, false), None);
+    phases.save(Literal::from(-1));
+    assert_eq!(phases.decide(1, false), Some(false));
+    phases.save(Literal::from(1));
+    assert_eq!(phases.decide(1, false), Some(true));
+}
+
+#[test]
+fn target_only_grows_and_is_used_when_stable() {
+    let mut phases = Phases::new(3);
+    phases.update(&trail(&[1, -2]));
+    phases.update(&trail(&[-1]));
+    phases.save(Literal::from(-1));
+    assert_eq!(phases.decide(1, true), Some(true));
+    assert_eq!(phases.decide(1, false), Some(false));
+    phases.reset_target();
+    phases.update(&trail(&[-1]));
+    assert_eq!(phases.decide(1, true), Some(false));
+}
+
+#[test]
+fn rephase_best_restores_longest_trail() {
+    let mut phases = Phases::new(3);
+    let mut rng = Rng::new(1);
+    phases.update(&trail(&[1, -2, 3]));
+    phases.reset_target();
+    phases.update(&trail(&[-1]));
+    for var in 1..=3 {
+        phases.set_saved(var, false);
+    }
+    phases.rephase(Rephase::Best, |_| true, &mut rng);
+    assert_eq!(phases.saved(1), Some(true));
+    assert_eq!(phases.saved(2), Some(false));
+    assert_eq!(phases.saved(3), Some(true));
+    // best is forgotten, the next shorter trail replaces it
+    phases.update(&trail(&[-3]));
+    phases.rephase(Rephase::Best, |_| true, &mut rng);
+    assert_eq!(phases.saved(3), Some(false));
+}
+
+#[test]
+fn rephase_original_inverted_flipped() {
+    let mut phases = Phases::new(2);
+    let mut rng = Rng::new(1);
+    let original = |var: LiteralSize| var == 1;
+    phases.rephase(Rephase::Original, original, &mut rng);
+    assert_eq!((phases.saved(1), phases.saved(2)), (Some(true), Some(false)));
+    phases.rephase(Rephase::Flipped, original, &mut rng);
+    assert_eq!((phases.saved(1), phases.saved(2)), (Some(false), Some(true)));
+    phases.rephase(Rephase::Inverted, original, &mut rng);
+    assert_eq!((phases.saved(1), phases.saved(2)), (Some(false), Some(true)));
+}
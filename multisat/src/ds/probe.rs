@@ -0,0 +1,182 @@
+use crate::ds::*;
+
+/// Hyper-binary resolvents start in tier 2, so the ones the search does not
+/// use are reduced again
+const RESOLVENT_LBD: usize = CORE_LBD + 1;
+
+/// Literals worth probing: roots of the binary implication graph, which imply
+/// other literals through binary clauses but are implied by none. A binary
+/// clause `a b` is the implications `-a -> b` and `-b -> a`.
+pub fn probe_roots(binaries: &[[Literal; 2]], num_variables: usize) -> Vec<Literal> {
+    // binary occurrences of each literal; a literal implies something iff its
+    // negation occurs and is implied by something iff it occurs itself
+    let mut occurs = vec![0usize; 2 * (num_variables + 1)];
+    for binary in binaries.iter() {
+        for lit in binary.iter() {
+            occurs[lit.index()] += 1;
+        }
+    }
+    let mut roots = Vec::new();
+    for var in 1..=num_variables {
+        for lit in [Literal::new(var, true), Literal::new(var, false)] {
+            if occurs[lit.invert().index()] > 0 && occurs[lit.index()] == 0 {
+                roots.push(lit);
+            }
+        }
+    }
+    roots
+}
+
+impl SolverState {
+    /// Failed literal probing. Each root of the binary implication graph is
+    /// assigned at level 1 in both phases and propagated: a conflict asserts the
+    /// opposite literal, literals implied by both phases are asserted too. A
+    /// literal implied through a longer clause gets the hyper-binary resolvent
+    /// `-probe implied` as a learned clause, unless it is there already, which
+    /// also lets equivalences show up in the binary implication graph. Stops
+    /// after `effort` propagation ticks.
+    pub fn probe(&mut self, effort: usize) {
+        assert!(self.level == 0);
+        if self.unsat_at_root {
+            return;
+        }
+        if let FormulaUnitProp::Conflict { .. } = self.unit_prop() {
+            self.unsat_at_root = true;
+            return;
+        }
+        let binaries: Vec<[Literal; 2]> = self
+            .clause_db
+            .iter()
+            .filter(|&cref| self.clause_db.clause_len(cref) == 2)
+            .map(|cref| {
+                let lits = self.clause_db.lits(cref);
+                [lits[0], lits[1]]
+            })
+            .collect();
+        let roots = probe_roots(&binaries, self.num_variables);
+
+        let limit = self.propagation_ticks + effort;
+        // literals implied by the positive phase of the variable being probed
+        let mut implied_by_pos: Vec<bool> = vec![false; 2 * (self.num_variables + 1)];
+        for root in roots {
+            if self.propagation_ticks > limit || self.unsat_at_root {
+                break;
+            }
+            let var = root.var();
+            if !self.assig.is_decidable(var) {
+                continue;
+            }
+            let pos = Literal::new(var, true);
+            let implied = match self.probe_literal(pos) {
+                Some(implied) => implied,
+                None => continue,
+            };
+            for lit in implied.iter() {
+                implied_by_pos[lit.index()] = true;
+            }
+            let common: Vec<Literal> = match self.probe_literal(pos.invert()) {
+                Some(implied_neg) => implied_neg
+                    .into_iter()
+                    .filter(|lit| implied_by_pos[lit.index()])
+                    .collect(),
+                None => Vec::new(),
+            };
+            for lit in implied.iter() {
+                implied_by_pos[lit.index()] = false;
+            }
+            for lit in common {
+                if literal_unassigned(&lit, &self.assig) {
+                    self.simplify_stats.common_implied += 1;
+                    self.assert_root_unit(lit);
+                }
+            }
+        }
+        self.finish_simplification(&[]);
+    }
+
+    /// Propagates `lit` at level 1 and returns the literals it implies, or None
+    /// if it failed (or was already assigned), in which case its negation is
+    /// asserted.
+    fn probe_literal(&mut self, lit: Literal) -> Option<Vec<Literal>> {
+        if self.unsat_at_root || !literal_unassigned(&lit, &self.assig) {
+            return None;
+        }
+        let start = self.trail.len();
+        self.add_decision(&Decision::make_choice(lit));
+        let res = self.unit_prop();
+        let implied: Vec<Literal> = self.trail[start + 1..]
+            .iter()
+            .map(|dec| dec.get_lit())
+            .collect();
+        let mut resolvents: Vec<[Literal; 2]> = Vec::new();
+        if res == FormulaUnitProp::Ok {
+            for dec in self.trail[start + 1..].iter() {
+                if let Decision::UnitProp {
+                    lit: implied,
+                    reason,
+                } = *dec
+                {
+                    if self.clause_db.clause_len(reason) > 2
+                        && !self.has_binary(lit.invert(), implied)
+                    {
+                        resolvents.push([lit.invert(), implied]);
+                    }
+                }
+            }
+        }
+        self.backtrack_keeping_phases();
+
+        if res != FormulaUnitProp::Ok {
+            self.simplify_stats.failed_literals += 1;
+            self.assert_root_unit(lit.invert());
+            return None;
+        }
+        for resolvent in resolvents {
+            self.simplify_stats.hyper_binary += 1;
+            self.add_clause(&resolvent, true, RESOLVENT_LBD);
+        }
+        Some(implied)
+    }
+
+    /// Whether the binary clause `a b` is already there
+    fn has_binary(&self, a: Literal, b: Literal) -> bool {
+        self.watchlist
+            .get_lit(&a)
+            .iter()
+            .any(|watch| watch.binary && watch.blocker == b)
+    }
+
+    /// Backtracks to level 0 from a probe without saving the phases of the
+    /// probed assignments, probing should not change where the search continues
+    pub(super) fn backtrack_keeping_phases(&mut self) {
+        let phases: Vec<(LiteralSize, bool)> = self.trail[self.trail_lim[0]..]
+            .iter()
+            .map(|dec| {
+                let var = dec.get_lit().var();
+                (var, self.decision_heuristic.saved_phase(var))
+            })
+            .collect();
+        self.backtrack_to_level(0);
+        for (var, phase) in phases {
+            self.decision_heuristic.set_saved_phase(var, phase);
+        }
+    }
+
+    /// Asserts a literal found during probing and propagates it at once so the
+    /// next probes see it
+    pub(super) fn assert_root_unit(&mut self, lit: Literal) {
+        if literal_falsified(&lit, &self.assig) {
+            self.unsat_at_root = true;
+            return;
+        }
+        if literal_unassigned(&lit, &self.assig) {
+            self.add_decision(&Decision::make_assertunit(lit));
+        }
+        if let FormulaUnitProp::Conflict { .. } = self.unit_prop() {
+            self.unsat_at_root = true;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
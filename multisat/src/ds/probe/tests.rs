@@ -0,0 +1,103 @@
+use super::*;
+use crate::test_util::{assert_unsat, clauses, state};
+
+fn value(s: &SolverState, lit: i32) -> Option<bool> {
+    s.assig
+        .get(&Literal::from(lit).var())
+        .map(|info| info.litsign == Literal::from(lit).sign())
+}
+
+#[test]
+fn roots_have_only_outgoing_implications() {
+    // -1 -> 2 -> 3, and -3 -> -2 -> 1
+    let binaries = [
+        [Literal::from(1), Literal::from(2)],
+        [Literal::from(-2), Literal::from(3)],
+    ];
+    let roots = probe_roots(&binaries, 3);
+    assert_eq!(roots, vec![Literal::from(-1), Literal::from(-3)]);
+}
+
+#[test]
+fn failed_literal_is_asserted() {
+    // -1 implies 2 and -2
+    let mut s = state(3, &clauses(&[&[1, 2], &[1, -2, 3], &[1, -2, -3]]));
+    s.probe(PREPROCESS_EFFORT);
+    assert_eq!(value(&s, 1), Some(true));
+    assert!(s.simplify_stats.failed_literals > 0);
+    assert!(!s.unsat_at_root);
+}
+
+#[test]
+fn common_implication_is_asserted() {
+    // 1 -> 3 since 8 is false, -1 -> 2 and 6 -> 3, none of it backwards
+    let mut s = state(
+        8,
+        &clauses(&[&[-8], &[-1, 8, 3], &[1, 2], &[1, 6], &[-2, -6, 3]]),
+    );
+    s.probe(PREPROCESS_EFFORT);
+    assert_eq!(value(&s, 3), Some(true));
+    assert_eq!(s.simplify_stats.common_implied, 1);
+}
+
+#[test]
+fn hyper_binary_resolvents_are_learned() {
+    // -1 -> 2, -1 -> 3 and 2 3 -> 4 through a ternary clause
+    let mut s = state(
+        5,
+        &clauses(&[&[1, 2], &[1, 3], &[-2, -3, 4], &[4, 5], &[-4, -5, 1]]),
+    );
+    s.probe(PREPROCESS_EFFORT);
+    assert!(s.simplify_stats.hyper_binary > 0);
+    let mut learned: Vec<Vec<i32>> = s
+        .clause_db
+        .iter()
+        .filter(|&cref| s.clause_db.is_learned(cref))
+        .map(|cref| {
+            let mut clause: Vec<i32> = s
+                .clause_db
+                .lits(cref)
+                .iter()
+                .map(|lit| lit.to_dimacs())
+                .collect();
+            clause.sort_unstable();
+            clause
+        })
+        .collect();
+    learned.sort();
+    assert!(learned.contains(&vec![1, 4]));
+}
+
+#[test]
+fn probing_finds_unsat() {
+    let mut s = state(3, &clauses(&[&[1, 2], &[1, -2], &[-1, 3], &[-1, -3]]));
+    assert_unsat(&mut s);
+}
+
+#[test]
+fn resolvents_are_reducible_and_not_duplicated() {
+    let mut s = state(
+        5,
+        &clauses(&[&[1, 2], &[1, 3], &[-2, -3, 4], &[4, 5], &[-4, -5, 1]]),
+    );
+    s.probe(PREPROCESS_EFFORT);
+    let learned = |s: &SolverState| -> Vec<ClauseRef> {
+        s.clause_db
+            .iter()
+            .filter(|&cref| s.clause_db.is_learned(cref))
+            .collect()
+    };
+    let first = learned(&s);
+    assert!(!first.is_empty());
+    for &cref in first.iter() {
+        assert!(s.clause_db.lbd(cref) > CORE_LBD);
+    }
+    assert!(s.has_binary(Literal::from(1), Literal::from(4)));
+    assert!(s.has_binary(Literal::from(4), Literal::from(1)));
+    assert!(!s.has_binary(Literal::from(1), Literal::from(5)));
+    // probing the same roots again finds the resolvents in place
+    let found = s.simplify_stats.hyper_binary;
+    s.probe(PREPROCESS_EFFORT);
+    assert_eq!(s.simplify_stats.hyper_binary, found);
+    assert_eq!(learned(&s).len(), first.len());
+}
@@ -0,0 +1,60 @@
+use crate::ds::utils::*;
+
+/// A clause removed by a simplification that keeps satisfiability but not every
+/// model. If the model does not satisfy it, making `witness` true repairs it
+/// without breaking any entry pushed earlier.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReconstructionEntry {
+    pub witness: Literal,
+    pub clause: Vec<Literal>,
+}
+
+/// The removed clauses in the order they were removed. A model of the simplified
+/// formula is turned into a model of the original one by going through them
+/// backwards.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReconstructionStack {
+    entries: Vec<ReconstructionEntry>,
+}
+
+impl ReconstructionStack {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, witness: Literal, clause: &[Literal]) {
+        debug_assert!(clause.contains(&witness));
+        self.entries.push(ReconstructionEntry {
+            witness,
+            clause: clause.to_vec(),
+        });
+    }
+
+    pub fn entries(&self) -> &[ReconstructionEntry] {
+        &self.entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Fixes up `values`, indexed by variable, so every removed clause holds
+    pub fn extend(&self, values: &mut [bool]) {
+        for entry in self.entries.iter().rev() {
+            if !entry
+                .clause
+                .iter()
+                .any(|lit| values[lit.var()] == lit.sign())
+            {
+                values[entry.witness.var()] = entry.witness.sign();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
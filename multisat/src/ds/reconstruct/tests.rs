@@ -0,0 +1,28 @@
+use super::*;
+use crate::test_util::lits;
+
+#[test]
+fn extend_flips_witness_of_falsified_clause() {
+    let mut stack = ReconstructionStack::new();
+    stack.push(Literal::from(1), &lits(&[1, 2]));
+    let mut values = vec![false; 3];
+    stack.extend(&mut values);
+    assert!(values[1]);
+    let mut values = vec![false, false, true];
+    stack.extend(&mut values);
+    assert!(!values[1]);
+}
+
+#[test]
+fn extend_goes_backwards() {
+    // the unit is pushed last so it is applied first, then -1 2 may undo it
+    let mut stack = ReconstructionStack::new();
+    stack.push(Literal::from(-1), &lits(&[-1, 2]));
+    stack.push(Literal::from(1), &lits(&[1]));
+    let mut values = vec![false; 3];
+    stack.extend(&mut values);
+    assert!(!values[1]);
+    let mut values = vec![false, false, true];
+    stack.extend(&mut values);
+    assert!(values[1]);
+}
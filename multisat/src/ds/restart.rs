@@ -0,0 +1,189 @@
+/// When the search goes back to the root (or, with trail reuse, close to it)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RestartPolicy {
+    /// Restart intervals that double up to a limit, after which they start over
+    /// from a slightly larger minimum
+    #[default]
+    Geometric,
+    /// Intervals following the Luby sequence
+    Luby,
+    /// Restart when recent learned clauses have a higher LBD than the long term
+    /// average, unless the trail is unusually long
+    Glucose,
+    /// Luby in stable mode, glucose in focused mode
+    Mode,
+}
+
+impl RestartPolicy {
+    /// Names accepted by `--restart`
+    pub const NAMES: &'static [&'static str] = &["geometric", "luby", "glucose", "mode"];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "geometric" => Some(RestartPolicy::Geometric),
+            "luby" => Some(RestartPolicy::Luby),
+            "glucose" => Some(RestartPolicy::Glucose),
+            "mode" => Some(RestartPolicy::Mode),
+            _ => None,
+        }
+    }
+}
+
+/// Conflicts per unit of the Luby sequence
+const LUBY_UNIT: usize = 100;
+const FAST_LBD_ALPHA: f64 = 1.0 / 32.0;
+const SLOW_LBD_ALPHA: f64 = 1.0 / 4096.0;
+const TRAIL_ALPHA: f64 = 1.0 / 4096.0;
+/// Glucose restarts when the fast LBD average exceeds the slow one by this factor
+const GLUCOSE_MARGIN: f64 = 1.25;
+/// Glucose never restarts after fewer conflicts than this
+const GLUCOSE_MIN_CONFLICTS: usize = 50;
+/// A trail this much longer than average suggests a model is close, so the next
+/// glucose restart is postponed
+const GLUCOSE_BLOCK_MARGIN: f64 = 1.4;
+const GLUCOSE_BLOCK_START: usize = 10000;
+
+/// `i`-th element (starting at 1) of the Luby sequence 1 1 2 1 1 2 4 1 1 2 ...
+pub fn luby(i: usize) -> usize {
+    let mut i = i;
+    loop {
+        // the smallest 2^k - 1 that is at least i
+        let mut size = 1;
+        while size < i {
+            size = 2 * size + 1;
+        }
+        if size == i {
+            return size / 2 + 1;
+        }
+        i -= size / 2;
+    }
+}
+
+/// Exponential moving average. Until 1/alpha values have been seen it is the plain
+/// average, so early values are not drowned by the initial zero.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ema {
+    value: f64,
+    alpha: f64,
+    count: usize,
+}
+
+impl Ema {
+    pub fn new(alpha: f64) -> Self {
+        Ema {
+            value: 0.0,
+            alpha,
+            count: 0,
+        }
+    }
+
+    pub fn update(&mut self, x: f64) {
+        self.count += 1;
+        let alpha = self.alpha.max(1.0 / self.count as f64);
+        self.value += alpha * (x - self.value);
+    }
+
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+}
+
+/// Restart schedule state for all policies
+#[derive(Debug, Clone, PartialEq)]
+pub struct Restarts {
+    pub policy: RestartPolicy,
+    conflicts_since_restart: usize,
+    min_interval: f32,
+    max_interval: f32,
+    cur_interval: f32,
+    luby_index: usize,
+    fast_lbd: Ema,
+    slow_lbd: Ema,
+    trail: Ema,
+    conflicts: usize,
+    pub restarts: usize,
+    /// Glucose restarts postponed because of a long trail
+    pub blocked: usize,
+    /// Restarts that kept some decision levels
+    pub reused_trails: usize,
+}
+
+impl Default for Restarts {
+    fn default() -> Self {
+        Restarts {
+            policy: RestartPolicy::default(),
+            conflicts_since_restart: 0,
+            min_interval: 16.0,
+            max_interval: 1024.0,
+            cur_interval: 16.0,
+            luby_index: 1,
+            fast_lbd: Ema::new(FAST_LBD_ALPHA),
+            slow_lbd: Ema::new(SLOW_LBD_ALPHA),
+            trail: Ema::new(TRAIL_ALPHA),
+            conflicts: 0,
+            restarts: 0,
+            blocked: 0,
+            reused_trails: 0,
+        }
+    }
+}
+
+impl Restarts {
+    pub fn new(policy: RestartPolicy) -> Self {
+        Restarts {
+            policy,
+            ..Default::default()
+        }
+    }
+
+    /// Records a conflict, `trail_len` is the number of assignments when it happened
+    pub fn on_conflict(&mut self, lbd: usize, trail_len: usize) {
+        self.conflicts += 1;
+        self.conflicts_since_restart += 1;
+        self.fast_lbd.update(lbd as f64);
+        self.slow_lbd.update(lbd as f64);
+        let long_trail = trail_len as f64 > GLUCOSE_BLOCK_MARGIN * self.trail.value();
+        self.trail.update(trail_len as f64);
+        if self.conflicts > GLUCOSE_BLOCK_START && long_trail && self.glucose_due() {
+            self.blocked += 1;
+            self.conflicts_since_restart = 0;
+        }
+    }
+
+    fn glucose_due(&self) -> bool {
+        self.conflicts_since_restart >= GLUCOSE_MIN_CONFLICTS
+            && self.fast_lbd.value() > GLUCOSE_MARGIN * self.slow_lbd.value()
+    }
+
+    pub fn should_restart(&self, stable: bool) -> bool {
+        match (self.policy, stable) {
+            (RestartPolicy::Geometric, _) => self.conflicts_since_restart as f32 > self.cur_interval,
+            (RestartPolicy::Luby, _) | (RestartPolicy::Mode, true) => {
+                self.conflicts_since_restart >= LUBY_UNIT * luby(self.luby_index)
+            }
+            (RestartPolicy::Glucose, _) | (RestartPolicy::Mode, false) => self.glucose_due(),
+        }
+    }
+
+    /// Moves the schedule on after a restart that `should_restart` asked for
+    pub fn restarted(&mut self, stable: bool) {
+        self.restarts += 1;
+        self.conflicts_since_restart = 0;
+        match (self.policy, stable) {
+            (RestartPolicy::Geometric, _) => {
+                if self.cur_interval <= self.max_interval {
+                    self.cur_interval *= 2.0;
+                } else {
+                    self.max_interval *= 1.2;
+                    self.min_interval *= 1.2;
+                    self.cur_interval = self.min_interval;
+                }
+            }
+            (RestartPolicy::Luby, _) | (RestartPolicy::Mode, true) => self.luby_index += 1,
+            (RestartPolicy::Glucose, _) | (RestartPolicy::Mode, false) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,73 @@
+use super::*;
+
+#[test]
+fn luby_sequence() {
+    let seq: Vec<usize> = (1..=15).map(luby).collect();
+    assert_eq!(seq, vec![1, 1, 2, 1, 1, 2, 4, 1, 1, 2, 1, 1, 2, 4, 8]);
+}
+
+#[test]
+fn ema_starts_as_plain_average() {
+    let mut ema = Ema::new(0.01);
+    ema.update(4.0);
+    assert_eq!(ema.value(), 4.0);
+    ema.update(2.0);
+    assert_eq!(ema.value(), 3.0);
+}
+
+#[test]
+fn luby_restarts_follow_sequence() {
+    let mut restarts = Restarts::new(RestartPolicy::Luby);
+    let mut intervals = Vec::new();
+    let mut since = 0;
+    while intervals.len() < 4 {
+        restarts.on_conflict(3, 10);
+        since += 1;
+        if restarts.should_restart(false) {
+            restarts.restarted(false);
+            intervals.push(since);
+            since = 0;
+        }
+    }
+    assert_eq!(intervals, vec![LUBY_UNIT, LUBY_UNIT, 2 * LUBY_UNIT, LUBY_UNIT]);
+}
+
+#[test]
+fn glucose_restarts_on_lbd_spike() {
+    let mut restarts = Restarts::new(RestartPolicy::Glucose);
+    for _ in 0..1000 {
+        restarts.on_conflict(4, 10);
+    }
+    assert!(!restarts.should_restart(false));
+    for _ in 0..20 {
+        restarts.on_conflict(20, 10);
+    }
+    assert!(restarts.should_restart(false));
+    restarts.restarted(false);
+    assert!(!restarts.should_restart(false));
+}
+
+#[test]
+fn glucose_blocks_restart_on_long_trail() {
+    let mut restarts = Restarts::new(RestartPolicy::Glucose);
+    for _ in 0..GLUCOSE_BLOCK_START {
+        restarts.on_conflict(4, 10);
+    }
+    for _ in 0..GLUCOSE_MIN_CONFLICTS {
+        restarts.on_conflict(20, 10);
+    }
+    assert!(restarts.should_restart(false));
+    restarts.on_conflict(20, 100);
+    assert_eq!(restarts.blocked, 1);
+    assert!(!restarts.should_restart(false));
+}
+
+#[test]
+fn mode_policy_depends_on_stability() {
+    let mut restarts = Restarts::new(RestartPolicy::Mode);
+    for _ in 0..LUBY_UNIT {
+        restarts.on_conflict(4, 10);
+    }
+    assert!(restarts.should_restart(true));
+    assert!(!restarts.should_restart(false));
+}
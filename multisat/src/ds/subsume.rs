@@ -0,0 +1,208 @@
+use rustc_hash::FxHashMap;
+
+use crate::ds::clause_db::ClauseRef;
+use crate::ds::*;
+
+/// Clauses longer than this are not used to subsume others
+const SUBSUME_CLAUSE_LIMIT: usize = 100;
+
+/// Bloom filter over the variables of a clause. If `c` subsumes or strengthens
+/// `d` then `signature(c) & !signature(d) == 0`.
+pub fn signature(lits: &[Literal]) -> u64 {
+    lits.iter()
+        .fold(0, |sig, lit| sig | 1u64 << (lit.var() % 64))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Subsumption {
+    None,
+    /// Every literal of `c` is in `d`
+    Subsumed,
+    /// `c` and `d` resolve on this literal of `d` to a clause that subsumes `d`,
+    /// so it can be removed from `d`
+    Strengthen(Literal),
+}
+
+/// How `c` relates to `d`. `marks` holds the literals of `c`.
+fn check(c_len: usize, d: &[Literal], marks: &[bool]) -> Subsumption {
+    let mut found = 0;
+    let mut flipped = None;
+    for &lit in d.iter() {
+        if marks[lit.index()] {
+            found += 1;
+        } else if marks[lit.invert().index()] {
+            if flipped.is_some() {
+                return Subsumption::None;
+            }
+            flipped = Some(lit);
+        }
+    }
+    match flipped {
+        None if found == c_len => Subsumption::Subsumed,
+        Some(lit) if found + 1 == c_len => Subsumption::Strengthen(lit),
+        _ => Subsumption::None,
+    }
+}
+
+/// Same as the check the pass does, for two clauses given directly
+pub fn subsumption(c: &[Literal], d: &[Literal]) -> Subsumption {
+    let max_index = c
+        .iter()
+        .chain(d.iter())
+        .map(|lit| lit.index().max(lit.invert().index()))
+        .max()
+        .unwrap_or(0);
+    let mut marks = vec![false; max_index + 1];
+    for lit in c.iter() {
+        marks[lit.index()] = true;
+    }
+    check(c.len(), d, &marks)
+}
+
+impl SolverState {
+    /// Removes subsumed clauses and strengthens clauses by self-subsuming
+    /// resolution, using occurrence lists and clause signatures. Learned clauses
+    /// take part if `include_learned` is set, a learned clause that subsumes an
+    /// irredundant one becomes irredundant. `effort` limits the literal visits.
+    pub fn subsume(&mut self, include_learned: bool, effort: usize) {
+        assert!(self.level == 0);
+        if self.unsat_at_root {
+            return;
+        }
+        let mut units: Vec<Literal> = Vec::new();
+        let mut occurs: Vec<Vec<ClauseRef>> = vec![Vec::new(); 2 * (self.num_variables + 1)];
+        let mut signatures: FxHashMap<ClauseRef, u64> = FxHashMap::default();
+        let mut candidates: Vec<ClauseRef> = Vec::new();
+        for cref in self.clause_db.refs() {
+            if !include_learned && self.clause_db.is_learned(cref) {
+                continue;
+            }
+            let cref = match self.strip_root_assigned(cref, &mut units) {
+                Some(cref) => cref,
+                None => continue,
+            };
+            let lits = self.clause_db.lits(cref);
+            for lit in lits.iter() {
+                occurs[lit.index()].push(cref);
+            }
+            signatures.insert(cref, signature(lits));
+            candidates.push(cref);
+        }
+        candidates.sort_by_key(|&cref| self.clause_db.clause_len(cref));
+        // shortest clauses first, strengthened clauses are pushed on top since they
+        // may subsume more now
+        candidates.reverse();
+
+        let mut marks: Vec<bool> = vec![false; 2 * (self.num_variables + 1)];
+        let mut spent = 0;
+        while let Some(cref) = candidates.pop() {
+            if spent > effort {
+                break;
+            }
+            if self.clause_db.is_deleted(cref)
+                || self.clause_db.clause_len(cref) > SUBSUME_CLAUSE_LIMIT
+            {
+                continue;
+            }
+            let c: Vec<Literal> = self.clause_db.lits(cref).to_vec();
+            let c_sig = signatures[&cref];
+            // every clause it subsumes or strengthens has this literal or its negation
+            let pivot = *c
+                .iter()
+                .min_by_key(|lit| occurs[lit.index()].len() + occurs[lit.invert().index()].len())
+                .unwrap();
+            for lit in c.iter() {
+                marks[lit.index()] = true;
+            }
+            for side in [pivot, pivot.invert()] {
+                occurs[side.index()].retain(|&d| !self.clause_db.is_deleted(d));
+                for d in occurs[side.index()].clone() {
+                    if d == cref
+                        || self.clause_db.is_deleted(d)
+                        || self.clause_db.clause_len(d) < c.len()
+                        || c_sig & !signatures[&d] != 0
+                    {
+                        continue;
+                    }
+                    spent += self.clause_db.clause_len(d);
+                    match check(c.len(), self.clause_db.lits(d), &marks) {
+                        Subsumption::None => {}
+                        Subsumption::Subsumed => {
+                            if !self.clause_db.is_learned(d) {
+                                self.clause_db.set_irredundant(cref);
+                            }
+                            self.clause_db.delete(d);
+                            self.simplify_stats.subsumed += 1;
+                        }
+                        Subsumption::Strengthen(removed) => {
+                            self.simplify_stats.strengthened += 1;
+                            let strengthened: Vec<Literal> = self
+                                .clause_db
+                                .lits(d)
+                                .iter()
+                                .copied()
+                                .filter(|&lit| lit != removed)
+                                .collect();
+                            let learned = self.clause_db.is_learned(d);
+                            let lbd = self.clause_db.lbd(d).min(strengthened.len());
+                            self.clause_db.delete(d);
+                            if strengthened.len() == 1 {
+                                units.push(strengthened[0]);
+                                continue;
+                            }
+                            let new = self.clause_db.add(&strengthened, learned, lbd);
+                            for lit in strengthened.iter() {
+                                occurs[lit.index()].push(new);
+                            }
+                            signatures.insert(new, signature(&strengthened));
+                            candidates.push(new);
+                        }
+                    }
+                }
+            }
+            for lit in c.iter() {
+                marks[lit.index()] = false;
+            }
+        }
+        self.finish_simplification(&units);
+    }
+
+    /// Deletes a clause satisfied at level 0 and replaces one with literals false
+    /// at level 0 by the clause without them. Units this leaves go to `units`.
+    fn strip_root_assigned(
+        &mut self,
+        cref: ClauseRef,
+        units: &mut Vec<Literal>,
+    ) -> Option<ClauseRef> {
+        let lits = self.clause_db.lits(cref);
+        if clause_satisfied(lits, &self.assig) {
+            self.clause_db.delete(cref);
+            return None;
+        }
+        if lits.iter().all(|lit| literal_unassigned(lit, &self.assig)) {
+            return Some(cref);
+        }
+        let stripped: Vec<Literal> = lits
+            .iter()
+            .copied()
+            .filter(|lit| literal_unassigned(lit, &self.assig))
+            .collect();
+        let learned = self.clause_db.is_learned(cref);
+        let lbd = self.clause_db.lbd(cref).min(stripped.len());
+        self.clause_db.delete(cref);
+        match stripped.len() {
+            0 => {
+                self.unsat_at_root = true;
+                None
+            }
+            1 => {
+                units.push(stripped[0]);
+                None
+            }
+            _ => Some(self.clause_db.add(&stripped, learned, lbd)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,91 @@
+use super::*;
+use crate::test_util::{assert_unsat, clauses, lits, state};
+
+fn sorted_clauses(s: &SolverState) -> Vec<Vec<i32>> {
+    let mut clauses: Vec<Vec<i32>> = s
+        .clauses()
+        .map(|clause| {
+            let mut clause: Vec<i32> = clause.iter().map(|lit| lit.to_dimacs()).collect();
+            clause.sort_unstable();
+            clause
+        })
+        .collect();
+    clauses.sort();
+    clauses
+}
+
+#[test]
+fn signature_filters_non_subsets() {
+    let c = signature(&lits(&[1, -2]));
+    assert_eq!(c & !signature(&lits(&[2, 1, 5])), 0);
+    assert_ne!(c & !signature(&lits(&[1, 3])), 0);
+    // variables 1 and 65 share a bit, the filter may let false candidates through
+    assert_eq!(signature(&lits(&[1])), signature(&lits(&[-65])));
+}
+
+#[test]
+fn classifies_clause_pairs() {
+    assert_eq!(
+        subsumption(&lits(&[1, -2]), &lits(&[3, -2, 1])),
+        Subsumption::Subsumed
+    );
+    assert_eq!(
+        subsumption(&lits(&[1, -2]), &lits(&[3, 2, 1])),
+        Subsumption::Strengthen(Literal::from(2))
+    );
+    assert_eq!(
+        subsumption(&lits(&[1, -2]), &lits(&[-1, 2, 3])),
+        Subsumption::None
+    );
+    assert_eq!(
+        subsumption(&lits(&[1, 4]), &lits(&[1, 2, 3])),
+        Subsumption::None
+    );
+}
+
+#[test]
+fn removes_subsumed_and_strengthens() {
+    let mut s = state(
+        5,
+        &clauses(&[
+            &[1, 2],
+            &[1, 2, 3],
+            &[4, 2, 1],
+            &[-1, 2, 5],
+            &[3, 4, 5],
+            &[-3, 4, 5, 1],
+        ]),
+    );
+    s.subsume(false, PREPROCESS_EFFORT);
+    assert_eq!(s.simplify_stats.subsumed, 2);
+    assert_eq!(s.simplify_stats.strengthened, 2);
+    // -1 2 5 with 1 2 gives 2 5, 3 4 5 with -3 4 5 1 gives 1 4 5
+    assert_eq!(
+        sorted_clauses(&s),
+        vec![vec![1, 2], vec![1, 4, 5], vec![2, 5], vec![3, 4, 5]]
+    );
+}
+
+#[test]
+fn strengthening_to_unit_is_asserted() {
+    let mut s = state(3, &clauses(&[&[1, 2], &[1, -2], &[-1, 2, 3]]));
+    s.subsume(false, PREPROCESS_EFFORT);
+    assert_eq!(s.get_model()[0], 1);
+    assert!(!s.unsat_at_root);
+}
+
+#[test]
+fn strengthening_finds_unsat() {
+    let mut s = state(2, &clauses(&[&[1, 2], &[1, -2], &[-1, 2], &[-1, -2]]));
+    assert_unsat(&mut s);
+}
+
+#[test]
+fn learned_clause_subsuming_original_becomes_irredundant() {
+    let mut s = state(4, &clauses(&[&[1, 2, 3], &[-1, 4], &[2, 4, -3]]));
+    s.clause_db.add(&lits(&[1, 2]), true, 2);
+    s.subsume(true, PREPROCESS_EFFORT);
+    assert_eq!(s.clause_db.num_learned(), 0);
+    assert!(sorted_clauses(&s).contains(&vec![1, 2]));
+    assert!(!sorted_clauses(&s).contains(&vec![1, 2, 3]));
+}
@@ -1,200 +1,144 @@
 use super::*;
+use crate::test_util::{lits, state};
 
 #[test]
 fn literal_struct_tests() {
     let l1 = Literal::make_new("12");
-    assert_eq!(
-        l1,
-        Literal {
-            var: 12,
-            sign: true
-        }
-    );
+    assert_eq!(l1, Literal::new(12, true));
+    assert_eq!((l1.var(), l1.sign()), (12, true));
 
     let l2 = Literal::make_new("-15");
-    assert_eq!(
-        l2,
-        Literal {
-            var: 15,
-            sign: false
-        }
-    );
+    assert_eq!(l2, Literal::new(15, false));
+    assert_eq!((l2.var(), l2.sign()), (15, false));
 
-    assert_eq!(l1.is_negative(), false);
-    assert_eq!(l2.is_negative(), true);
+    assert!(!l1.is_negative());
+    assert!(l2.is_negative());
 
-    assert_eq!(
-        l1.invert(),
-        Literal {
-            var: 12,
-            sign: false
-        }
-    );
-    assert_eq!(
-        l2.invert(),
-        Literal {
-            var: 15,
-            sign: true
-        }
-    );
+    assert_eq!(l1.invert(), Literal::new(12, false));
+    assert_eq!(l2.invert(), Literal::new(15, true));
+    assert_eq!(l2.to_dimacs(), -15);
+}
+
+#[test]
+fn literal_encoding_test() {
+    let pos = Literal::from(7);
+    let neg = Literal::from(-7);
+    assert_eq!(pos.code(), 14);
+    assert_eq!(neg.code(), 15);
+    assert_eq!(pos.index() ^ 1, neg.index());
+    assert_eq!(Literal::from_code(15), neg);
 }
 
 #[test]
 fn literal_falsified_tests() {
-    let assig = HashMap::from([
-        (
-            1,
-            AssigInfo {
-                litsign: true,
-                level: 0,
-            },
-        ),
-        (
-            2,
-            AssigInfo {
-                litsign: false,
-                level: 5,
-            },
-        ),
-    ]);
-    assert!(!literal_falsified(&Literal { var: 1, sign: true }, &assig));
-    assert!(literal_falsified(
-        &Literal {
-            var: 1,
-            sign: false
-        },
-        &assig
-    ));
+    let mut assig = Assig::new(3);
+    assig.insert(1, AssigInfo::new(true, 0));
+    assig.insert(2, AssigInfo::new(false, 5));
 
-    assert!(!literal_falsified(
-        &Literal {
-            var: 2,
-            sign: false
-        },
-        &assig
-    ));
-    assert!(literal_falsified(&Literal { var: 2, sign: true }, &assig));
-
-    assert!(!literal_falsified(
-        &Literal {
-            var: 3,
-            sign: false
-        },
-        &assig
-    ));
-    assert!(!literal_falsified(&Literal { var: 3, sign: true }, &assig));
+    assert!(!literal_falsified(&Literal::from(1), &assig));
+    assert!(literal_falsified(&Literal::from(-1), &assig));
+
+    assert!(!literal_falsified(&Literal::from(-2), &assig));
+    assert!(literal_falsified(&Literal::from(2), &assig));
+
+    assert!(!literal_falsified(&Literal::from(-3), &assig));
+    assert!(!literal_falsified(&Literal::from(3), &assig));
 }
 
 #[test]
-fn make_clause_test() {
-    let c_raw1 = vec!["-1", "19", "-8", "0"];
-    let c1 = Clause {
-        literals: vec![
-            Literal {
-                var: 1,
-                sign: false,
-            },
-            Literal {
-                var: 19,
-                sign: true,
-            },
-            Literal {
-                var: 8,
-                sign: false,
-            },
-        ],
-        w1: 0,
-        w2: 1,
-    };
-    assert_eq!(Clause::make_clause(c_raw1), c1);
+fn clause_db_add_delete_test() {
+    let mut db = ClauseDB::new();
+    let c1 = db.add(&lits(&[-1, 19, -8]), false, 0);
+    let c2 = db.add(&lits(&[2, 3]), true, 4);
+    assert_eq!(db.lits(c1), &lits(&[-1, 19, -8])[..]);
+    assert_eq!(db.lits(c2), &lits(&[2, 3])[..]);
+    assert!(!db.is_learned(c1) && db.is_learned(c2));
+    assert_eq!(db.lbd(c2), 4);
+    db.set_lbd(c2, 2);
+    assert_eq!(db.lbd(c2), 2);
+    assert!(db.is_learned(c2));
+    db.set_activity(c2, 1.5);
+    assert_eq!(db.activity(c2), 1.5);
+    assert_eq!((db.len(), db.num_learned()), (2, 1));
+
+    db.delete(c1);
+    assert!(db.is_deleted(c1));
+    assert_eq!((db.len(), db.num_learned()), (1, 1));
+    assert_eq!(db.refs(), vec![c2]);
+    assert!(db.wasted() > 0);
+}
+
+#[test]
+fn clause_db_collect_garbage_test() {
+    let mut db = ClauseDB::new();
+    let c1 = db.add(&lits(&[1, 2, 3]), false, 0);
+    db.add(&lits(&[-4, 5]), true, 3);
+    db.delete(c1);
+    let before = db.arena_len();
+    db.collect_garbage();
+    assert_eq!(db.wasted(), 0);
+    assert!(db.arena_len() < before);
+    let refs = db.refs();
+    assert_eq!(refs.len(), 1);
+    assert_eq!(db.lits(refs[0]), &lits(&[-4, 5])[..]);
+    assert_eq!(db.lbd(refs[0]), 3);
+    assert!(db.is_learned(refs[0]));
 }
 
 #[test]
 fn unit_prop_conflict_test() {
-    let mut clause = Clause::try_from(vec![1, -2]).unwrap();
-    let mut assig: Assig = HashMap::new();
-    assig.insert(
-        1,
-        AssigInfo {
-            litsign: false,
-            level: 2,
-        },
-    );
-    assig.insert(
-        2,
-        AssigInfo {
-            litsign: true,
-            level: 1,
-        },
-    );
+    let mut clause = lits(&[1, -2]);
+    let mut assig = Assig::new(2);
+    assig.insert(1, AssigInfo::new(false, 2));
+    assig.insert(2, AssigInfo::new(true, 1));
 
-    let result = clause.unit_prop(&assig, &Literal::from(1));
+    let result = clause_unit_prop(&mut clause, &assig, &Literal::from(1));
     match result {
-        ClauseUnitProp::Conflict => assert!(true),
-        _ => assert!(false, "Expected conflict but got {:?}", result),
+        ClauseUnitProp::Conflict => {}
+        _ => panic!("Expected conflict but got {:?}", result),
     }
 }
 
 #[test]
 fn unit_prop_reassigned_test() {
-    let mut clause = Clause::try_from(vec![-1, 3, -19]).unwrap();
-    let mut assig: Assig = HashMap::new();
-    assig.insert(
-        1,
-        AssigInfo {
-            litsign: true,
-            level: 2,
-        },
-    );
+    let mut clause = lits(&[-1, 3, -19]);
+    let mut assig = Assig::new(19);
+    assig.insert(1, AssigInfo::new(true, 2));
 
-    let result = clause.unit_prop(&assig, &Literal::from(-1));
+    let result = clause_unit_prop(&mut clause, &assig, &Literal::from(-1));
     match result {
-        ClauseUnitProp::Reassigned{old_watch: _,new_watch: _} => assert!(clause.w1 == 2),
-        _ => assert!(false, "Expected reassigned but got {:?}", result),
+        ClauseUnitProp::Reassigned { new_watch } => {
+            assert_eq!(new_watch, Literal::from(-19));
+            assert_eq!(clause, lits(&[3, -19, -1]));
+        }
+        _ => panic!("Expected reassigned but got {:?}", result),
     }
 }
 
 #[test]
 fn unit_prop_new_unit_test() {
-    let mut clause = Clause::try_from(vec![-11, -22]).unwrap();
-    let mut assig: Assig = HashMap::new();
-    assig.insert(
-        22,
-        AssigInfo {
-            litsign: true,
-            level: 2,
-        },
-    );
+    let mut clause = lits(&[-11, -22]);
+    let mut assig = Assig::new(22);
+    assig.insert(22, AssigInfo::new(true, 2));
 
-    let result = clause.unit_prop(&assig, &Literal::from(-22));
+    let result = clause_unit_prop(&mut clause, &assig, &Literal::from(-22));
     match result {
         ClauseUnitProp::Unit { lit } => assert_eq!(lit, Literal::from(-11)),
-        _ => assert!(false, "Expected new unit but got {:?}", result),
+        _ => panic!("Expected new unit but got {:?}", result),
     }
 }
 
 #[test]
 fn unit_prop_satisfied_test() {
-    let mut clause = Clause::try_from(vec![-11, -22]).unwrap();
-    let mut assig: Assig = HashMap::new();
-    assig.insert(
-        22,
-        AssigInfo {
-            litsign: false,
-            level: 2,
-        },
-    );
-    assig.insert(
-        11,
-        AssigInfo {
-            litsign: true,
-            level: 3,
-        },
-    );
-    let result = clause.unit_prop(&assig, &Literal::from(-11));
+    let mut clause = lits(&[-11, -22]);
+    let mut assig = Assig::new(22);
+    assig.insert(22, AssigInfo::new(false, 2));
+    assig.insert(11, AssigInfo::new(true, 3));
+
+    let result = clause_unit_prop(&mut clause, &assig, &Literal::from(-11));
     match result {
-        ClauseUnitProp::Satisfied => assert!(true),
-        _ => assert!(false, "Expected new unit but got {:?}", result),
+        ClauseUnitProp::Satisfied => {}
+        _ => panic!("Expected satisfied but got {:?}", result),
     }
 }
 
@@ -202,144 +146,379 @@ fn unit_prop_satisfied_test() {
 fn solver_state_new() {
     let s = SolverState::make_new(5);
     assert_eq!(s.level, 0);
-    assert!(s.decision_stack.is_empty());
+    assert!(s.trail.is_empty());
     assert!(s.assig.is_empty());
 }
 
 #[test]
 fn add_decision_test() {
     let mut s = SolverState::make_new(10);
-    let lit = Literal::from(-16);
-    let d = Decision {
-        lit: lit,
-        unit_prop_idx: None,
-    };
-    let expected_assig = AssigInfo {
-        litsign: lit.sign,
-        level: 1,
-    };
+    let lit = Literal::from(-6);
+    let d = Decision::make_choice(lit);
     s.add_decision_prop(&d);
-    assert_eq!(s.decision_stack[0], d);
-    let actual_assig = s.assig.get(&lit.var);
-    assert!(actual_assig.map_or(false, |&a| a == expected_assig));
+    assert_eq!(s.trail[0], d);
+    assert_eq!(s.trail_lim, vec![0]);
+    assert_eq!(
+        s.assig.get(&lit.var()),
+        Some(&AssigInfo::new(lit.sign(), 1))
+    );
     assert!(s.level == 1);
 }
 
 #[test]
 fn add_unit_test() {
     let mut s = SolverState::make_new(10);
-    let lit = Literal::from(20);
-    let d = Decision {
-        lit: lit,
-        unit_prop_idx: Some(21),
-    };
-    let expected_assig = AssigInfo {
-        litsign: lit.sign,
-        level: s.level,
-    };
+    s.add_raw_clause(lits(&[-9, 4]));
+    let lit = Literal::from(4);
+    let d = Decision::make_unitprop(lit, s.clause_db.refs()[0]);
     s.add_decision_prop(&d);
-    assert_eq!(s.decision_stack[0], d);
-    let actual_assig = s.assig.get(&lit.var);
-    assert!(actual_assig.map_or(false, |&a| a == expected_assig));
+    assert_eq!(s.trail[0], d);
+    assert!(s.trail_lim.is_empty());
+    assert_eq!(
+        s.assig.get(&lit.var()),
+        Some(&AssigInfo::new(lit.sign(), 0))
+    );
     assert!(s.level == 0);
 }
 
 #[test]
-fn test_pop_decision() {
+fn test_backtrack_to_level() {
     let mut s = SolverState::make_new(10);
-    let d1 = Decision {
-        lit: Literal::from(-9),
-        unit_prop_idx: None,
-    };
-    let d2 = Decision {
-        lit: Literal::from(20),
-        unit_prop_idx: Some(42),
-    };
-    s.add_decision_prop(&d1);
-    s.add_decision_prop(&d2);
-    assert!(s.level == 1);
-    assert_eq!(s.pop_decision(), d2);
+    s.add_raw_clause(lits(&[9, 7]));
+    s.add_raw_clause(lits(&[-7, -3, 2]));
+    let d1 = Decision::make_choice(Literal::from(-9));
+    let d2 = Decision::make_choice(Literal::from(3));
+    assert_eq!(s.add_decision_prop(&d1), FormulaUnitProp::Ok);
+    assert_eq!(s.add_decision_prop(&d2), FormulaUnitProp::Ok);
+    assert!(s.level == 2);
+    let crefs = s.clause_db.refs();
+    assert_eq!(
+        s.trail,
+        vec![
+            d1.clone(),
+            Decision::make_unitprop(Literal::from(7), crefs[0]),
+            d2,
+            Decision::make_unitprop(Literal::from(2), crefs[1])
+        ]
+    );
+    s.backtrack_to_level(1);
     assert!(s.level == 1);
-    assert_eq!(s.pop_decision(), d1);
+    assert_eq!(s.trail.len(), 2);
+    assert!(s.assig.get(&3).is_none() && s.assig.get(&2).is_none());
+    s.backtrack_to_level(0);
     assert!(s.level == 0);
+    assert!(s.trail.is_empty() && s.assig.is_empty());
+    assert_eq!(s.prop_head, 0);
 }
 
 #[test]
-fn test_add_raw_clause() { 
+fn test_add_raw_clause() {
     let mut s = SolverState::make_new(15);
-    let c_raw1 = vec!["-3","5","-7","0"];
-    let c1 = Clause::try_from(vec![-3,5,-7]).unwrap();
-    s.add_raw_clause(c_raw1);
-    assert_eq!(s.clauses[0], c1);
-    assert_eq!(s.watchlist.watchlist.len(),0);
-    s.setup_watchlist();
-    assert_eq!(*s.watchlist.get(3).false_watch.iter().next().unwrap(), 0);
-    assert_eq!(*s.watchlist.get(5).true_watch.iter().next().unwrap(), 0);
-
-
-    let c_raw2 = vec!["-2","0"]; 
-    s.add_raw_clause(c_raw2);
-    assert_eq!(s.clauses.len(),1) ; //clause len does not change
-    assert_eq!(s.decision_stack.len(), 0); //decision stack does not change
-    assert_eq!(*s.assig.get( &2).unwrap(), AssigInfo{litsign: false, level: 0});
+    s.add_raw_clause(lits(&[-3, 5, -7]));
+    let cref = s.clause_db.refs()[0];
+    assert_eq!(s.clause_db.lits(cref), &lits(&[-3, 5, -7])[..]);
+    assert_eq!(
+        s.watchlist.get_lit(&Literal::from(-3)),
+        &vec![Watch::new(Literal::from(5), cref, false)]
+    );
+    assert_eq!(
+        s.watchlist.get_lit(&Literal::from(5)),
+        &vec![Watch::new(Literal::from(-3), cref, false)]
+    );
+    assert!(s.watchlist.get_lit(&Literal::from(-7)).is_empty());
 
+    s.add_raw_clause(lits(&[-2]));
+    assert_eq!(s.num_clauses(), 1); //clause len does not change
+    assert_eq!(s.trail, vec![Decision::make_assertunit(Literal::from(-2))]);
+    assert_eq!(*s.assig.get(&2).unwrap(), AssigInfo::new(false, 0));
 }
 
 #[test]
 fn test_pure_literal_elimination() {
     //2 is the only pure literal
-    let c1 = Clause::try_from(vec![1,-2,3]).unwrap();
-    let c2 = Clause::try_from(vec![-1,-3]).unwrap();
-    let c3 = Clause::try_from(vec![-2,5]).unwrap();
-    let c4 = Clause::try_from(vec![-3,-5]).unwrap();
     let mut s = SolverState::make_new(5);
-    let expected_clauses = vec![c2.clone(),c4.clone()];
-    for c in vec![c1,c2,c3,c4] {
-        s.add_clause(c);
+    for c in [&[1, -2, 3][..], &[-1, -3], &[-2, 5], &[-3, -5]] {
+        s.add_raw_clause(lits(c));
     }
     s.pure_literal_elimination();
-    assert_eq!(s.clauses, expected_clauses); // not sure if this equality is right 
+    s.remove_marked_clauses();
+    let remaining: Vec<Vec<Literal>> = s.clauses().map(|c| c.to_vec()).collect();
+    assert_eq!(remaining, vec![lits(&[-1, -3]), lits(&[-3, -5])]);
 }
 
 #[test]
-fn test_formula_unit_prop_duplicate_units() { 
-    let c1 = Clause::try_from(vec![-1,-2]).unwrap();
-    let c2 = Clause::try_from(vec![-1,-3,-2]).unwrap();
-
+fn test_formula_unit_prop_duplicate_units() {
     let mut s = SolverState::make_new(3);
-    s.add_clause(c1);
-    s.add_clause(c2);
-    s.setup_watchlist();
-    let d1 =  Decision::new(3, None);
-    s.add_decision_prop(&d1);
-    assert_eq!(s.unit_prop(&d1),FormulaUnitProp::Ok);
-    let d2 = Decision::new(1,None);
-    s.add_decision_prop(&d2);
-    assert_eq!(s.unit_prop(&d2),FormulaUnitProp::Ok);
+    s.add_raw_clause(lits(&[-1, -2]));
+    s.add_raw_clause(lits(&[-1, -3, -2]));
+    let d1 = Decision::make_choice(Literal::from(3));
+    assert_eq!(s.add_decision_prop(&d1), FormulaUnitProp::Ok);
+    let d2 = Decision::make_choice(Literal::from(1));
+    assert_eq!(s.add_decision_prop(&d2), FormulaUnitProp::Ok);
     // Assignments are now 1,-2,3
-    assert_eq!(HashSet::from_iter(s.get_model()), HashSet::from([1,-2,3]));
+    assert_eq!(s.get_model(), vec![1, -2, 3]);
+}
+
+#[test]
+fn test_watchlist_reassigned_correctly() {
+    let mut s = SolverState::make_new(3);
+    s.add_raw_clause(lits(&[-1, -2]));
+    s.add_raw_clause(lits(&[-1, -3, -2]));
+    let cref = s.clause_db.refs()[1];
+
+    assert!(!s.watchlist.watches_clause(&Literal::from(-2), cref));
+    assert!(s.watchlist.watches_clause(&Literal::from(-3), cref));
+
+    let d1 = Decision::make_choice(Literal::from(3));
+    assert_eq!(s.add_decision_prop(&d1), FormulaUnitProp::Ok);
+
+    assert!(s.watchlist.watches_clause(&Literal::from(-2), cref));
+    assert!(!s.watchlist.watches_clause(&Literal::from(-3), cref));
+}
 
+#[test]
+fn test_unit_prop_detects_conflict_in_queue() {
+    // -1 implies both 2 and -2, the second implication has to be a conflict even
+    // though 2 has not been propagated yet
+    let mut s = SolverState::make_new(3);
+    s.add_raw_clause(lits(&[1, 2]));
+    s.add_raw_clause(lits(&[1, -2, 3]));
+    s.add_raw_clause(lits(&[1, -3, -2]));
+    let d = Decision::make_choice(Literal::from(-1));
+    assert!(matches!(
+        s.add_decision_prop(&d),
+        FormulaUnitProp::Conflict { .. }
+    ));
 }
 
 #[test]
-fn test_watchlist_reassigned_correctly(){
+fn test_learn_first_uip() {
+    let mut s = SolverState::make_new(5);
+    s.add_raw_clause(lits(&[-1, 2]));
+    s.add_raw_clause(lits(&[-2, -3, 4]));
+    s.add_raw_clause(lits(&[-4, 5]));
+    s.add_raw_clause(lits(&[-4, -5, -2]));
+    assert_eq!(
+        s.add_decision_prop(&Decision::make_choice(Literal::from(1))),
+        FormulaUnitProp::Ok
+    );
+    let conflict = match s.add_decision_prop(&Decision::make_choice(Literal::from(3))) {
+        FormulaUnitProp::Conflict { conflict_cause } => conflict_cause,
+        FormulaUnitProp::Ok => panic!("expected a conflict"),
+    };
+    let learnt = s.learn_first_uip(conflict);
+    // 4 is the first UIP, the rest of the clause comes from level 1
+    assert_eq!(learnt[0], Literal::from(-4));
+    assert_eq!(learnt.len(), 2);
+    assert!(learnt.contains(&Literal::from(-2)));
+    assert!(s.seen.iter().all(|&marked| !marked));
+}
+
+#[test]
+fn test_binary_watch_propagates_blocker() {
+    let mut s = SolverState::make_new(3);
+    s.add_raw_clause(lits(&[-1, 2]));
+    s.add_raw_clause(lits(&[-1, -2]));
+    let bin = s.clause_db.refs()[0];
+    assert_eq!(
+        s.watchlist.get_lit(&Literal::from(-1))[0],
+        Watch::new(Literal::from(2), bin, true)
+    );
+    let d = Decision::make_choice(Literal::from(1));
+    assert!(matches!(
+        s.add_decision_prop(&d),
+        FormulaUnitProp::Conflict { .. }
+    ));
+    assert_eq!(s.trail[1], Decision::make_unitprop(Literal::from(2), bin));
+}
+
+#[test]
+fn test_satisfied_clause_updates_blocker() {
+    let mut s = SolverState::make_new(4);
+    s.add_raw_clause(lits(&[1, 2, 3, 4]));
+    let cref = s.clause_db.refs()[0];
+    // moves the watches to 3 and then 4, the watch on 3 keeps the stale blocker 1
+    for lit in [-2, -1, 4, -3] {
+        let d = Decision::make_choice(Literal::from(lit));
+        assert_eq!(s.add_decision_prop(&d), FormulaUnitProp::Ok);
+    }
+    // the clause was found satisfied by 4 when -3 was propagated
+    assert_eq!(
+        s.watchlist.get_lit(&Literal::from(3)),
+        &vec![Watch::new(Literal::from(4), cref, false)]
+    );
+}
+
+#[test]
+fn clause_db_relocation_test() {
+    let mut db = ClauseDB::new();
+    let c1 = db.add(&lits(&[1, 2, 3]), false, 0);
+    let c2 = db.add(&lits(&[-4, 5]), true, 3);
+    let c3 = db.add(&lits(&[6, -7]), true, 2);
+    db.delete(c2);
+    let reloc = db.collect_garbage();
+    assert_eq!(reloc.get(c1), Some(c1));
+    assert_eq!(reloc.get(c2), None);
+    let new_c3 = reloc.get(c3).unwrap();
+    assert_eq!(db.lits(new_c3), &lits(&[6, -7])[..]);
+    assert_eq!(db.lbd(new_c3), 2);
+}
+
+#[test]
+fn test_reduce_db_keeps_core_and_reasons() {
+    let mut s = SolverState::make_new(8);
+    s.add_raw_clause(lits(&[1, 2, 3]));
+    let core = s.add_clause(&lits(&[-1, 4]), true, 2);
+    let reason = s.add_clause(&lits(&[5, -6, -7]), true, 9);
+    let mut local = Vec::new();
+    for (idx, clause) in [[-5, 8, 2], [-8, 3, 1], [4, 6, -2], [7, -3, 6]]
+        .iter()
+        .enumerate()
+    {
+        let cref = s.add_clause(&lits(clause), true, 8);
+        s.clause_db.set_activity(cref, idx as f32);
+        local.push(cref);
+    }
+    s.add_decision_prop(&Decision::make_choice(Literal::from(6)));
+    s.add_decision_prop(&Decision::make_choice(Literal::from(7)));
+    assert_eq!(
+        s.trail[2],
+        Decision::make_unitprop(Literal::from(5), reason)
+    );
+    let core_lits = s.clause_db.lits(core).to_vec();
+    let reason_lits = s.clause_db.lits(reason).to_vec();
+
+    s.reduce_db();
+    // the two least active local clauses are gone
+    assert_eq!(s.clause_db.len(), 5);
+    assert_eq!(s.clause_db.num_learned(), 4);
+    let remaining: Vec<Vec<Literal>> = s.clauses().map(|c| c.to_vec()).collect();
+    assert!(remaining.contains(&core_lits));
+    assert!(remaining.contains(&reason_lits));
+    // the reason on the trail was relocated
+    match s.trail[2] {
+        Decision::UnitProp { reason, .. } => {
+            assert_eq!(s.clause_db.lits(reason), &reason_lits[..])
+        }
+        ref d => panic!("unexpected {:?}", d),
+    }
+}
 
-    let c1 = Clause::try_from(vec![-1,-2]).unwrap();
-    let c2 = Clause::try_from(vec![-1,-3,-2]).unwrap();
+fn decide_all(s: &mut SolverState, decisions: &[i32]) {
+    for &lit in decisions {
+        let d = Decision::make_choice(Literal::from(lit));
+        assert_eq!(s.add_decision_prop(&d), FormulaUnitProp::Ok);
+    }
+}
 
+#[test]
+fn test_minimize_shrinks_level_to_uip() {
     let mut s = SolverState::make_new(3);
-    s.add_clause(c1);
-    s.add_clause(c2);
-    s.setup_watchlist();
-    let d1 =  Decision::new(3, None);
-    s.add_decision_prop(&d1);
+    s.add_raw_clause(lits(&[-1, 2]));
+    decide_all(&mut s, &[1, 3]);
+    let mut learnt = lits(&[-3, -1, -2]);
+    s.minimize_learnt(&mut learnt);
+    assert_eq!(learnt, lits(&[-3, -1]));
+    assert_eq!(s.minimize_stats.shrunk, 1);
+    assert!(s.seen.iter().all(|&marked| !marked));
+}
 
-    assert!(!s.watchlist.get(2).false_watch.contains(&(1)));
-    assert!(s.watchlist.get(3).false_watch.contains(&(1)));
+#[test]
+fn test_minimize_recursive() {
+    let mut s = SolverState::make_new(7);
+    s.add_raw_clause(lits(&[-1, 2]));
+    s.add_raw_clause(lits(&[-2, -3, 5]));
+    decide_all(&mut s, &[1, 3, 7]);
+    // 5 is implied by 2 and 3, and 2 by 1. Shrinking level 2 would bring in -2
+    let mut learnt = lits(&[-7, -1, -3, -5]);
+    s.minimize_learnt(&mut learnt);
+    learnt[1..].sort();
+    assert_eq!(learnt, lits(&[-7, -1, -3]));
+    assert_eq!(s.minimize_stats.shrunk, 0);
+    assert_eq!(s.minimize_stats.recursive, 1);
+    assert!(s.seen.iter().all(|&marked| !marked));
+}
 
-    assert_eq!(s.unit_prop(&d1),FormulaUnitProp::Ok);
+#[test]
+fn test_minimize_binary_implication() {
+    let mut s = SolverState::make_new(4);
+    s.add_raw_clause(lits(&[-3, 1]));
+    decide_all(&mut s, &[1, 4, 3]);
+    // (-3 or 1) resolves -1 away
+    let mut learnt = lits(&[-3, -1, -4]);
+    s.minimize_learnt(&mut learnt);
+    assert_eq!(learnt, lits(&[-3, -4]));
+    assert_eq!(s.minimize_stats.binary, 1);
+    assert_eq!(s.minimize_stats.learnt_literals, 3);
+}
 
-    assert!(s.watchlist.get(2).false_watch.contains(&(1)));
-    assert!(!s.watchlist.get(3).false_watch.contains(&(1)));
-  
-}
\ No newline at end of file
+#[test]
+fn test_backtrack_keeps_out_of_order_literal() {
+    let mut s = SolverState::make_new(5);
+    s.options.chrono_threshold = Some(0);
+    decide_all(&mut s, &[1, 2, 3]);
+    let cref = s.add_clause(&lits(&[4, -1]), false, 0);
+    s.add_decision(&Decision::make_unitprop(Literal::from(4), cref));
+    assert_eq!(s.assig.get(&4).unwrap().level, 1);
+
+    s.backtrack_to_level(1);
+    assert_eq!(
+        s.trail,
+        vec![
+            Decision::make_choice(Literal::from(1)),
+            Decision::make_unitprop(Literal::from(4), cref)
+        ]
+    );
+    assert_eq!(s.trail_pos[4], 1);
+    // the kept literal is propagated again
+    assert_eq!(s.prop_head, 1);
+    assert!(s.assig.get(&2).is_none() && s.assig.get(&3).is_none());
+}
+
+#[test]
+fn test_chrono_solves_pigeonhole() {
+    // 4 pigeons in 3 holes, var 3 * p + h + 1 puts pigeon p in hole h
+    let var = |p: i32, h: i32| 3 * p + h + 1;
+    let mut clauses: Vec<Vec<Literal>> = Vec::new();
+    for p in 0..4 {
+        clauses.push(lits(&[var(p, 0), var(p, 1), var(p, 2)]));
+    }
+    for h in 0..3 {
+        for p in 0..4 {
+            for q in p + 1..4 {
+                clauses.push(lits(&[-var(p, h), -var(q, h)]));
+            }
+        }
+    }
+    let mut s = state(12, &clauses);
+    s.options.chrono_threshold = Some(0);
+    assert!(matches!(
+        crate::solve_with_assumptions(&mut s, &[]),
+        crate::CNFStatus::UNSAT
+    ));
+    assert!(s.chrono_backtracks > 0);
+}
+
+#[test]
+fn test_conflict_with_single_literal_at_level_propagates() {
+    let mut s = SolverState::make_new(4);
+    decide_all(&mut s, &[1, 4, 2, 3]);
+    // false at levels 1, 3 and 4, watched on the two lowest
+    let cref = s.add_clause(&lits(&[-1, -2, -3]), true, 3);
+    let num_clauses = s.clause_db.len();
+    match s.analyze_conflict_backtrack(cref) {
+        ConflictAnalysisResult::Backtrack { decision } => {
+            assert_eq!(decision, Decision::make_unitprop(Literal::from(-3), cref))
+        }
+        ConflictAnalysisResult::UNSAT => panic!("formula is satisfiable"),
+    }
+    // nothing is learned, the clause asserts -3 at level 3
+    assert_eq!(s.clause_db.len(), num_clauses);
+    assert_eq!(s.level, 3);
+    assert_eq!(s.assig.get(&3).unwrap().level, 3);
+    assert_eq!(s.clause_db.lits(cref), &lits(&[-3, -2, -1])[..]);
+    assert!(s.watchlist.watches_clause(&Literal::from(-3), cref));
+    assert!(s.watchlist.watches_clause(&Literal::from(-2), cref));
+    assert!(!s.watchlist.watches_clause(&Literal::from(-1), cref));
+    assert_eq!(s.conflicts, 1);
+}
@@ -1,9 +1,15 @@
 use std::{fmt, vec};
 
-#[derive(Clone, PartialEq, Eq, Hash, Copy)]
+use crate::ds::clause_db::ClauseRef;
+
+pub type LiteralSize = usize;
+
+/// A literal packed into 32 bits as `2 * var + is_negative`, so the two literals
+/// of a variable are adjacent and a literal can index per-literal tables directly.
+#[derive(Clone, PartialEq, Eq, Hash, Copy, PartialOrd, Ord)]
+#[repr(transparent)]
 pub struct Literal {
-    pub var: LiteralSize,
-    pub sign: bool,
+    code: u32,
 }
 
 impl fmt::Display for Literal {
@@ -13,8 +19,8 @@ impl fmt::Display for Literal {
         // stream: `f`. Returns `fmt::Result` which indicates whether the
         // operation succeeded or failed. Note that `write!` uses syntax which
         // is very similar to `println!`.
-        let sign = if self.sign { "" } else { "-" };
-        write!(f, "{}{}", sign, self.var)
+        let sign = if self.sign() { "" } else { "-" };
+        write!(f, "{}{}", sign, self.var())
     }
 }
 impl fmt::Debug for Literal {
@@ -24,35 +30,89 @@ impl fmt::Debug for Literal {
 }
 impl From<i32> for Literal {
     fn from(i: i32) -> Self {
-        Literal {
-            var: i.unsigned_abs() as LiteralSize,
-            sign: i.is_positive(),
-        }
+        Literal::new(i.unsigned_abs() as LiteralSize, i.is_positive())
     }
 }
 impl Literal {
+    pub fn new(var: LiteralSize, sign: bool) -> Self {
+        Literal {
+            code: ((var as u32) << 1) | (!sign as u32),
+        }
+    }
     pub fn make_new(n: &str) -> Self {
         let tmp: i32 = n.parse().unwrap();
         if tmp == 0 {
             panic!("literal cannot be 0")
         }
-        Literal {
-            var: tmp.unsigned_abs() as LiteralSize,
-            sign: tmp.is_positive(),
-        }
+        Literal::from(tmp)
+    }
+    pub fn from_code(code: u32) -> Self {
+        Literal { code }
     }
 
+    #[inline(always)]
+    pub fn var(&self) -> LiteralSize {
+        (self.code >> 1) as LiteralSize
+    }
+    #[inline(always)]
+    pub fn sign(&self) -> bool {
+        self.code & 1 == 0
+    }
+    /// Position of the literal in per-literal tables such as the watch lists
+    #[inline(always)]
+    pub fn index(&self) -> usize {
+        self.code as usize
+    }
+    pub fn code(&self) -> u32 {
+        self.code
+    }
     pub fn is_negative(&self) -> bool {
-        !self.sign
+        !self.sign()
     }
+    #[inline(always)]
     pub fn invert(&self) -> Literal {
         Literal {
-            var: self.var,
-            sign: !self.sign,
+            code: self.code ^ 1,
+        }
+    }
+    pub fn to_dimacs(&self) -> i32 {
+        if self.sign() {
+            self.var() as i32
+        } else {
+            -(self.var() as i32)
         }
     }
 }
 
+/// Xorshift generator, enough for the solver's randomized choices and
+/// reproducible from a seed
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng { state: seed.max(1) }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state
+    }
+
+    /// A number in `0..n`, n must not be 0
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    pub fn coin(&mut self) -> bool {
+        self.next_u64() & 1 == 1
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Copy)]
 pub struct AssigInfo {
     pub litsign: bool,
@@ -69,6 +129,14 @@ impl AssigInfo {
 pub struct Assig {
     pub assn: Vec<Option<AssigInfo>>,
     ln: usize,
+    /// Number of conflicts so far, the clock for `assigned_at`
+    time: u64,
+    /// When each variable was last assigned, kept after it is unassigned
+    assigned_at: Vec<u64>,
+    /// Variables removed from the formula by simplification, they are never
+    /// assigned during search and get their value from model reconstruction
+    eliminated: Vec<bool>,
+    num_eliminated: usize,
 }
 
 impl Assig {
@@ -76,15 +144,52 @@ impl Assig {
         Self {
             assn: vec![None; num_vars + 1],
             ln: 0,
+            time: 0,
+            assigned_at: vec![0; num_vars + 1],
+            eliminated: vec![false; num_vars + 1],
+            num_eliminated: 0,
         }
     }
 
+    pub fn time(&self) -> u64 {
+        self.time
+    }
+
+    /// Advances the clock, called once per conflict
+    pub fn tick(&mut self) {
+        self.time += 1;
+    }
+
+    pub fn assigned_at(&self, var: LiteralSize) -> u64 {
+        self.assigned_at[var]
+    }
+
+    pub fn eliminate(&mut self, var: LiteralSize) {
+        debug_assert!(self.assn[var].is_none() && !self.eliminated[var]);
+        self.eliminated[var] = true;
+        self.num_eliminated += 1;
+    }
+
+    pub fn is_eliminated(&self, var: LiteralSize) -> bool {
+        self.eliminated[var]
+    }
+
+    pub fn num_eliminated(&self) -> usize {
+        self.num_eliminated
+    }
+
+    /// Whether the search may still decide on `var`
+    pub fn is_decidable(&self, var: LiteralSize) -> bool {
+        self.assn[var].is_none() && !self.eliminated[var]
+    }
+
     pub fn get(&self, var: &LiteralSize) -> Option<&AssigInfo> {
         self.assn[*var].as_ref()
     }
 
     pub fn insert(&mut self, var: LiteralSize, assig_info: AssigInfo) {
         self.assn[var] = Some(assig_info);
+        self.assigned_at[var] = self.time;
         self.ln += 1;
     }
 
@@ -112,86 +217,87 @@ impl Assig {
     pub fn len(&self) -> usize {
         self.ln
     }
+
+    pub fn is_empty(&self) -> bool {
+        self.ln == 0
+    }
+
+    pub fn add_var(&mut self) {
+        self.assn.push(None);
+        self.assigned_at.push(0);
+        self.eliminated.push(false);
+    }
 }
 #[inline(always)]
 pub fn literal_falsified(lit: &Literal, assig: &Assig) -> bool {
-    match assig.assn[lit.var] {
-        Some(b) => b.litsign != lit.sign,
+    match assig.assn[lit.var()] {
+        Some(b) => b.litsign != lit.sign(),
         None => false,
     }
 }
 
+#[inline(always)]
 pub fn literal_satisfied(lit: &Literal, assig: &Assig) -> bool {
-    match assig.assn[lit.var] {
-        Some(b) => b.litsign == lit.sign,
+    match assig.assn[lit.var()] {
+        Some(b) => b.litsign == lit.sign(),
         None => false,
     }
 }
 pub fn literal_unassigned(lit: &Literal, assig: &Assig) -> bool {
-    assig.assn[lit.var].is_none()
+    assig.assn[lit.var()].is_none()
 }
 
-pub fn check_clause_watch_invariant(clause: &Clause, assig: &Assig) -> bool {
-    assert!(clause.w1 != clause.w2);
-    assert!(
-        !(literal_falsified(&clause.literals[clause.w1], assig)
-            && literal_falsified(&clause.literals[clause.w2], assig))
-    );
+pub fn check_clause_watch_invariant(clause: &[Literal], assig: &Assig) -> bool {
+    assert!(!(literal_falsified(&clause[0], assig) && literal_falsified(&clause[1], assig)));
     true
 }
-pub fn check_single_unit(clause: &Clause, assig: &Assig, lit_idx: usize) -> bool {
+pub fn check_single_unit(clause: &[Literal], assig: &Assig, lit_idx: usize) -> bool {
     assert!(
-        lit_idx < clause.literals.len(),
+        lit_idx < clause.len(),
         "idx {} len {}",
         lit_idx,
-        clause.literals.len()
+        clause.len()
     );
     assert_eq!(
         clause
-            .literals
             .iter()
             .filter(|lit| literal_falsified(lit, assig))
             .count(),
-        clause.literals.len() - 1
+        clause.len() - 1
     );
     let unassigned_lit: Vec<Literal> = clause
-        .literals
         .iter()
         .cloned()
         .filter(|lit| literal_unassigned(lit, assig))
         .collect();
-    let cond = unassigned_lit.len() == 1;
-    if !cond {
-        assert!(
-            false,
-            "unassigned lit {:?} clause {:?} assigs: {:?}",
-            unassigned_lit,
-            clause,
-            print_clause_lit_assigs(clause, assig)
-        );
-    }
-    assert!(unassigned_lit[0] == clause.literals[lit_idx]);
+    assert!(
+        unassigned_lit.len() == 1,
+        "unassigned lit {:?} clause {:?} assigs: {:?}",
+        unassigned_lit,
+        clause,
+        print_clause_lit_assigs(clause, assig)
+    );
+    assert!(unassigned_lit[0] == clause[lit_idx]);
     true
 }
 
 pub fn print_lit_assig(lit: &Literal, assig: &Assig) -> String {
-    match assig.get(&lit.var) {
-        Some(inf) => format!("Lit:{}:{},level:{}", lit.var, inf.litsign, inf.level),
+    match assig.get(&lit.var()) {
+        Some(inf) => format!("Lit:{}:{},level:{}", lit.var(), inf.litsign, inf.level),
         None => format!("Lit:{}:None", lit),
     }
 }
-pub fn print_clause_lit_assigs(clause: &Clause, assig: &Assig) -> String {
+pub fn print_clause_lit_assigs(clause: &[Literal], assig: &Assig) -> String {
     let mut tmp = String::new();
-    for lit in clause.literals.iter() {
-        tmp.push_str(&format!("({:?}:{:?})", lit, assig.get(&lit.var)));
+    for lit in clause.iter() {
+        tmp.push_str(&format!("({:?}:{:?})", lit, assig.get(&lit.var())));
     }
     tmp
 }
-pub fn print_non_falsified_lits(clause: &Clause, assig: &Assig) -> String {
+pub fn print_non_falsified_lits(clause: &[Literal], assig: &Assig) -> String {
     let mut tmpstr = String::new();
 
     let tmp: Vec<Literal> = clause
-        .literals
         .iter()
         .cloned()
         .filter(|lit| !literal_falsified(lit, assig))
@@ -204,9 +310,8 @@ pub fn print_non_falsified_lits(clause: &Clause, assig: &Assig) -> String {
     tmpstr
 }
 
-fn check_all_falsified(clause: &Clause, assig: &Assig) -> bool {
+fn check_all_falsified(clause: &[Literal], assig: &Assig) -> bool {
     let tmp: Vec<Literal> = clause
-        .literals
         .iter()
         .cloned()
         .filter(|lit| !literal_falsified(lit, assig))
@@ -216,209 +321,110 @@ fn check_all_falsified(clause: &Clause, assig: &Assig) -> bool {
         "tmp is {:?} non false: {:?} watch1_assig: {:?} watch2_assig: {:?}",
         tmp,
         print_non_falsified_lits(clause, assig),
-        print_lit_assig(&clause.literals[clause.w1], assig),
-        print_lit_assig(&clause.literals[clause.w2], assig)
+        print_lit_assig(&clause[0], assig),
+        print_lit_assig(&clause[1], assig)
     );
     true
 }
 
-#[derive(Debug, Clone, PartialEq)]
-pub struct Clause {
-    pub literals: Vec<Literal>,
-    pub w1: usize,
-    pub w2: usize,
-    pub deleted: bool,
-    pub conflict: bool,
-    pub lbd: usize,
+pub fn clause_satisfied(clause: &[Literal], assig: &Assig) -> bool {
+    clause.iter().any(|lit| literal_satisfied(lit, assig))
+}
+
+/// Moves two non-falsified literals (if there are any) to the watched positions 0 and 1
+pub fn set_unassigned_watches(clause: &mut [Literal], assig: &Assig) {
+    let mut next = 0;
+    for idx in 0..clause.len() {
+        if next == 2 {
+            break;
+        }
+        if !literal_falsified(&clause[idx], assig) {
+            clause.swap(next, idx);
+            next += 1;
+        }
+    }
 }
 
 #[derive(Debug)]
 pub enum ClauseUnitProp {
-    Reassigned {
-        old_watch: Literal,
-        new_watch: Literal,
-    },
-    Unit {
-        lit: Literal,
-    },
+    Reassigned { new_watch: Literal },
+    Unit { lit: Literal },
     Conflict,
     Satisfied,
 }
 
-#[derive(PartialEq, Debug)]
-pub enum FormulaUnitProp {
-    Ok,
-    Conflict { conflict_cause_idx: usize },
-}
-
-impl TryFrom<Vec<Literal>> for Clause {
-    type Error = &'static str;
-    fn try_from(vc: Vec<Literal>) -> Result<Self, Self::Error> {
-        if vc.len() < 2 {
-            return Err("Must have at least 2 literals");
-        }
-        let lits = vc.into_iter().map(Literal::from).collect();
-        Result::Ok(Self {
-            literals: lits,
-            w1: 0,
-            w2: 1,
-            deleted: false,
-            conflict: false,
-            lbd: 0,
-        })
-    }
-}
-
-impl Clause {
-    pub fn make_clause(raw_clause: Vec<&str>) -> Self {
-        assert!(raw_clause.len() >= 2);
-        let lits: Vec<Literal> = raw_clause
-            .iter()
-            .take_while(|&&lit| !lit.eq("0"))
-            .map(|raw_lit| Literal::make_new(raw_lit))
-            .collect();
+/// Visits a clause after its watched literal `lit` was falsified. Watched literals
+/// are always kept at positions 0 and 1; on return `lit` sits at position 1 unless
+/// the watch moved to `new_watch`.
+// #[inline(always)]
+pub fn clause_unit_prop(clause: &mut [Literal], assig: &Assig, lit: &Literal) -> ClauseUnitProp {
+    debug_assert!(literal_falsified(lit, assig));
 
-        Self {
-            literals: lits,
-            w1: 0,
-            w2: 1,
-            deleted: false,
-            conflict: false,
-            lbd: 0,
-        }
-    }
-    pub fn clause_satisfied(&self, assig: &Assig) -> bool {
-        self.literals
-            .iter()
-            .any(|lit| literal_satisfied(lit, assig))
+    if clause[0] == *lit {
+        clause.swap(0, 1);
     }
+    debug_assert!(clause[1] == *lit);
+    let other_watch_lit = clause[0];
 
-    pub fn reset_watch(&mut self, assig: &Assig, cur_idx: usize) -> Option<usize> {
-        debug_assert!(cur_idx == self.w1 || cur_idx == self.w2);
-        if !literal_falsified(&self.literals[cur_idx], assig) {
-            return Some(cur_idx);
-        }
-        for (idx, lit) in self.literals.iter().enumerate() {
-            if idx != self.w1 && idx != self.w2 && !literal_falsified(lit, assig) {
-                if cur_idx == self.w1 {
-                    self.w1 = idx;
-                } else {
-                    self.w2 = idx;
-                }
-                return Some(idx);
-            }
-        }
-        None
-    }
-   
-   
-    pub fn reset_watch_nocheck(&mut self, assig: &Assig, cur_idx: usize) -> Option<usize> {
-        debug_assert!(cur_idx == self.w1 || cur_idx == self.w2);
-        debug_assert!(literal_falsified(&self.literals[cur_idx], assig));
-        for (idx, lit) in self.literals.iter().enumerate() {
-            if idx != self.w1 && idx != self.w2 && !literal_falsified(lit, assig) {
-                if cur_idx == self.w1 {
-                    self.w1 = idx;
-                } else {
-                    self.w2 = idx;
-                }
-                return Some(idx);
-            }
-        }
-        None
+    if literal_satisfied(&other_watch_lit, assig) {
+        return ClauseUnitProp::Satisfied;
     }
 
-    pub fn set_unassigned_watches(&mut self, assig: &Assig) {
-        let mut c = 0;
-        if self.reset_watch(assig, self.w1).is_none() {
-            c += 1;
-        }
-
-        if self.reset_watch(assig, self.w2).is_none() {
-            c += 1;
+    for idx in 2..clause.len() {
+        if !literal_falsified(&clause[idx], assig) {
+            clause.swap(1, idx);
+            debug_assert!(check_clause_watch_invariant(clause, assig));
+            return ClauseUnitProp::Reassigned {
+                new_watch: clause[1],
+            };
         }
-        debug_assert!(c == 0);
-
-        debug_assert!(check_clause_watch_invariant(self, assig));
     }
+    if literal_falsified(&other_watch_lit, assig) {
+        // the other watch was falsified by an assignment that is still waiting
+        // on the trail to be propagated
+        debug_assert!(check_all_falsified(clause, assig));
+        ClauseUnitProp::Conflict
+    } else {
+        debug_assert!(check_single_unit(clause, assig, 0));
 
-    // #[inline(always)]
-    pub fn unit_prop(&mut self, assig: &Assig, lit: &Literal) -> ClauseUnitProp {
-        debug_assert!(literal_falsified(lit, assig));
-       
-        let (cur_idx, oidx) = if self.literals[self.w1] == *lit {
-            (self.w1, self.w2)
-        } else {
-            debug_assert!(self.literals[self.w2] == *lit);
-            (self.w2, self.w1)
-        };
-        let other_watch_lit = self.literals[oidx];
-
-        if let Some(b) = assig.assn[other_watch_lit.var] {
-            if b.litsign == other_watch_lit.sign {
-                debug_assert!(check_clause_watch_invariant(self, assig));
-                return ClauseUnitProp::Satisfied;
-            } else {
-                debug_assert!(check_all_falsified(self, assig));
-                return ClauseUnitProp::Conflict;
-            }
-        }
-
-        if let Some(nidx) = self.reset_watch_nocheck(assig, cur_idx) {
-            debug_assert!(!literal_falsified(&self.literals[nidx], assig));
-            debug_assert!(check_clause_watch_invariant(self, assig));
-
-            ClauseUnitProp::Reassigned {
-                old_watch: *lit,
-                new_watch: self.literals[nidx],
-            }
-        } else {
-            debug_assert!(check_single_unit(self, assig, oidx));
-            debug_assert!(check_clause_watch_invariant(self, assig));
-
-            ClauseUnitProp::Unit {
-                lit: other_watch_lit,
-            }
+        ClauseUnitProp::Unit {
+            lit: other_watch_lit,
         }
     }
 }
 
-pub type LiteralSize = usize;
-
-#[derive(Debug, Clone, PartialEq)]
-pub struct VarWatch {
-    false_watch: Vec<usize>,
-    true_watch: Vec<usize>,
+#[derive(PartialEq, Debug)]
+pub enum FormulaUnitProp {
+    Ok,
+    Conflict { conflict_cause: ClauseRef },
 }
-impl Default for VarWatch {
-    fn default() -> Self {
-        Self::new()
-    }
+
+/// A watch of a clause. `blocker` is another literal of the clause: while it is
+/// true the clause is satisfied and can be skipped without reading it. For a
+/// binary clause the blocker is the other literal, so the clause itself never
+/// has to be looked at during propagation.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Watch {
+    pub blocker: Literal,
+    pub cref: ClauseRef,
+    pub binary: bool,
 }
 
-impl VarWatch {
-    pub fn new() -> Self {
+impl Watch {
+    pub fn new(blocker: Literal, cref: ClauseRef, binary: bool) -> Self {
         Self {
-            false_watch: Vec::new(),
-            true_watch: Vec::new(),
+            blocker,
+            cref,
+            binary,
         }
     }
-    pub fn remove_idx(&mut self, lit: bool, watch_idx: usize) {
-        match lit {
-            true => self.true_watch.swap_remove(watch_idx),
-            false => self.false_watch.swap_remove(watch_idx),
-        };
-    }
-    pub fn add_clause(&mut self, lit: bool, clause_idx: usize) {
-        match lit {
-            true => self.true_watch.push(clause_idx),
-            false => self.false_watch.push(clause_idx),
-        };
-    }
 }
+
+/// Clauses watching each literal, indexed by `Literal::index`. A clause is
+/// visited when one of its two watched literals becomes false.
 #[derive(PartialEq, Debug)]
 pub struct WatchList {
-    pub watchlist: Vec<VarWatch>,
+    pub watchlist: Vec<Vec<Watch>>,
 }
 impl WatchList {
     pub fn empty() -> Self {
@@ -427,44 +433,59 @@ impl WatchList {
         }
     }
     pub fn clear(&mut self) {
-        self.watchlist.iter_mut().for_each(|vlist| {
-            vlist.true_watch.clear();
-            vlist.false_watch.clear()
-        });
+        self.watchlist.iter_mut().for_each(|wlist| wlist.clear());
     }
     pub fn new(num_vars: usize) -> Self {
         Self {
-            watchlist: vec![VarWatch::new(); num_vars + 1],
+            watchlist: vec![Vec::new(); 2 * (num_vars + 1)],
         }
     }
-    pub fn add_to_list(&mut self, lit: &Literal, clause_idx: usize) {
-        if lit.sign {
-            self.watchlist[lit.var].true_watch.push(clause_idx);
-        } else {
-            self.watchlist[lit.var].false_watch.push(clause_idx);
-        }
+    pub fn add_to_list(&mut self, lit: &Literal, watch: Watch) {
+        self.watchlist[lit.index()].push(watch);
     }
 
-    #[allow(dead_code)]
-    pub fn get(&self, idx: usize) -> &VarWatch {
-        &self.watchlist[idx]
+    /// Watches the first two literals of `clause`, each one blocked by the other
+    pub fn watch_clause(&mut self, clause: &[Literal], cref: ClauseRef) {
+        let binary = clause.len() == 2;
+        self.add_to_list(&clause[0], Watch::new(clause[1], cref, binary));
+        self.add_to_list(&clause[1], Watch::new(clause[0], cref, binary));
     }
-    pub fn get_lit(&self, lit: &Literal) -> &Vec<usize> {
-        match lit.sign {
-            true => &self.watchlist[lit.var].true_watch,
-            false => &self.watchlist[lit.var].false_watch,
+
+    /// Removes the watches `watch_clause` added
+    pub fn unwatch_clause(&mut self, clause: &[Literal], cref: ClauseRef) {
+        for lit in clause[..2].iter() {
+            self.watchlist[lit.index()].retain(|watch| watch.cref != cref);
         }
     }
 
-    pub fn remove_watch(&mut self, lit: &Literal, watch_idx: usize) {
-        self.watchlist[lit.var].remove_idx(lit.sign, watch_idx);
+    pub fn add_var(&mut self) {
+        self.watchlist.push(Vec::new());
+        self.watchlist.push(Vec::new());
+    }
+
+    pub fn get_lit(&self, lit: &Literal) -> &Vec<Watch> {
+        &self.watchlist[lit.index()]
+    }
+
+    pub fn watches_clause(&self, lit: &Literal, cref: ClauseRef) -> bool {
+        self.get_lit(lit).iter().any(|watch| watch.cref == cref)
+    }
+
+    /// Takes the list of `lit` out so it can be rewritten while other lists are updated
+    pub fn take_lit(&mut self, lit: &Literal) -> Vec<Watch> {
+        std::mem::take(&mut self.watchlist[lit.index()])
+    }
+
+    pub fn put_lit(&mut self, lit: &Literal, wlist: Vec<Watch>) {
+        debug_assert!(self.watchlist[lit.index()].is_empty());
+        self.watchlist[lit.index()] = wlist;
     }
 }
 
 #[derive(Clone, PartialEq, Debug, Eq)]
 pub enum Decision {
     AssertUnit { lit: Literal },
-    UnitProp { lit: Literal, unit_prop_idx: usize },
+    UnitProp { lit: Literal, reason: ClauseRef },
     Choice { lit: Literal },
 }
 
@@ -472,8 +493,8 @@ impl Decision {
     pub fn make_assertunit(lit: Literal) -> Self {
         Self::AssertUnit { lit }
     }
-    pub fn make_unitprop(lit: Literal, unit_prop_idx: usize) -> Self {
-        Self::UnitProp { lit, unit_prop_idx }
+    pub fn make_unitprop(lit: Literal, reason: ClauseRef) -> Self {
+        Self::UnitProp { lit, reason }
     }
     pub fn make_choice(lit: Literal) -> Self {
         Self::Choice { lit }
@@ -0,0 +1,107 @@
+use crate::ds::clause_db::ClauseRef;
+use crate::ds::*;
+
+impl SolverState {
+    /// Vivification: for each tier2 learned clause and each irredundant clause,
+    /// the negations of its literals are assigned one at a time and propagated
+    /// without the clause itself. A conflict means the literals assigned so far
+    /// are enough, a literal of the clause that becomes true means the assigned
+    /// ones plus that literal are, and literals that become false can be dropped.
+    /// Learned clauses get half of the `effort` in propagation ticks, then the
+    /// irredundant ones get the rest. Returns the number of shortened clauses.
+    pub fn vivify(&mut self, effort: usize) -> usize {
+        assert!(self.level == 0);
+        if self.unsat_at_root {
+            return 0;
+        }
+        let mut learned: Vec<ClauseRef> = Vec::new();
+        let mut irredundant: Vec<ClauseRef> = Vec::new();
+        for cref in self.clause_db.iter() {
+            if self.clause_db.clause_len(cref) < 3 {
+                continue;
+            }
+            if !self.clause_db.is_learned(cref) {
+                irredundant.push(cref);
+                continue;
+            }
+            let lbd = self.clause_db.lbd(cref);
+            if lbd > CORE_LBD && lbd <= TIER2_LBD {
+                learned.push(cref);
+            }
+        }
+        learned.sort_by_key(|&cref| self.clause_db.lbd(cref));
+        // longer clauses have more to lose
+        irredundant.sort_by_key(|&cref| std::cmp::Reverse(self.clause_db.clause_len(cref)));
+
+        let mut shortened = 0;
+        let start = self.propagation_ticks;
+        for (candidates, budget) in [(learned, effort / 2), (irredundant, effort)] {
+            for cref in candidates {
+                if self.propagation_ticks - start > budget || self.unsat_at_root {
+                    break;
+                }
+                if self.clause_db.is_deleted(cref) {
+                    continue;
+                }
+                if self.vivify_clause(cref) {
+                    shortened += 1;
+                }
+            }
+        }
+        self.simplify_stats.vivified += shortened;
+        self.finish_simplification(&[]);
+        shortened
+    }
+
+    /// Vivifies one clause, replacing it by the shorter one if there is one
+    fn vivify_clause(&mut self, cref: ClauseRef) -> bool {
+        let clause = self.clause_db.lits(cref).to_vec();
+        if clause_satisfied(&clause, &self.assig) {
+            self.clause_db.delete(cref);
+            return false;
+        }
+        // the clause must not propagate for itself
+        self.watchlist.unwatch_clause(&clause, cref);
+        let mut kept: Vec<Literal> = Vec::new();
+        let mut shorter = false;
+        for &lit in clause.iter() {
+            if literal_satisfied(&lit, &self.assig) {
+                kept.push(lit);
+                shorter = kept.len() < clause.len();
+                break;
+            }
+            if literal_falsified(&lit, &self.assig) {
+                shorter = true;
+                continue;
+            }
+            kept.push(lit);
+            self.add_decision(&Decision::make_choice(lit.invert()));
+            if let FormulaUnitProp::Conflict { .. } = self.unit_prop() {
+                shorter = kept.len() < clause.len();
+                break;
+            }
+        }
+        if self.level > 0 {
+            self.backtrack_keeping_phases();
+        }
+
+        if !shorter {
+            self.watchlist.watch_clause(&clause, cref);
+            return false;
+        }
+        let learned = self.clause_db.is_learned(cref);
+        let lbd = self.clause_db.lbd(cref).min(kept.len());
+        self.clause_db.delete(cref);
+        if kept.is_empty() {
+            self.unsat_at_root = true;
+        } else if kept.len() == 1 {
+            self.assert_root_unit(kept[0]);
+        } else {
+            self.add_clause(&kept, learned, lbd);
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests;
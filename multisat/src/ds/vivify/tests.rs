@@ -0,0 +1,56 @@
+use super::*;
+use crate::test_util::{clauses, state};
+
+fn has_clause(s: &SolverState, raw: &[i32]) -> bool {
+    s.clauses().any(|clause| {
+        clause.len() == raw.len() && raw.iter().all(|&n| clause.contains(&Literal::from(n)))
+    })
+}
+
+#[test]
+fn implied_literal_shortens_clause() {
+    // -1 and -2 imply 3 through 5, so 4 is not needed
+    let mut s = state(5, &clauses(&[&[1, 2, 3, 4], &[1, 2, 5], &[-5, 3]]));
+    assert_eq!(s.vivify(PREPROCESS_EFFORT), 1);
+    assert!(has_clause(&s, &[1, 2, 3]));
+    assert!(!has_clause(&s, &[1, 2, 3, 4]));
+    assert!(s.check_watch_invariant());
+}
+
+#[test]
+fn conflict_shortens_clause() {
+    // -1 and -2 imply 5 and 6, which conflict
+    let mut s = state(
+        6,
+        &clauses(&[&[1, 2, 3, 4], &[1, 2, 5], &[-5, -6], &[1, 6]]),
+    );
+    s.vivify(PREPROCESS_EFFORT);
+    assert!(has_clause(&s, &[1, 2]));
+    assert!(!has_clause(&s, &[1, 2, 3, 4]));
+}
+
+#[test]
+fn clause_does_not_vivify_itself() {
+    let mut s = state(4, &clauses(&[&[1, 2, 3], &[-1, 4], &[-2, -4]]));
+    assert_eq!(s.vivify(PREPROCESS_EFFORT), 0);
+    assert_eq!(s.num_clauses(), 3);
+    assert!(s.check_watch_invariant());
+}
+
+#[test]
+fn learned_clauses_stay_learned() {
+    let mut s = state(5, &clauses(&[&[1, 2, 5], &[-5, 3], &[3, 4, -1]]));
+    s.add_clause(
+        &[
+            Literal::from(1),
+            Literal::from(2),
+            Literal::from(3),
+            Literal::from(4),
+        ],
+        true,
+        TIER2_LBD,
+    );
+    s.vivify(PREPROCESS_EFFORT);
+    assert!(has_clause(&s, &[1, 2, 3]));
+    assert_eq!(s.clause_db.num_learned(), 1);
+}
@@ -0,0 +1,118 @@
+use crate::ds::utils::*;
+
+/// Chance in percent of flipping a random literal of the picked clause instead of
+/// the one that breaks the fewest clauses
+const WALK_NOISE: usize = 50;
+
+/// WalkSAT local search. Starting from `values`, indexed by variable, it
+/// repeatedly flips a variable of a random falsified clause, preferring flips that
+/// falsify no other clause. Afterwards `values` holds the assignment with the
+/// fewest falsified clauses that was reached, and that number is returned.
+pub fn walk(clauses: &[Vec<Literal>], values: &mut [bool], max_flips: usize, rng: &mut Rng) -> usize {
+    let mut occurrences: Vec<Vec<usize>> = vec![Vec::new(); 2 * values.len()];
+    let mut true_count: Vec<usize> = vec![0; clauses.len()];
+    for (idx, clause) in clauses.iter().enumerate() {
+        for lit in clause.iter() {
+            occurrences[lit.index()].push(idx);
+            if values[lit.var()] == lit.sign() {
+                true_count[idx] += 1;
+            }
+        }
+    }
+    let mut falsified = FalsifiedSet::new(clauses.len());
+    for (idx, &count) in true_count.iter().enumerate() {
+        if count == 0 {
+            falsified.insert(idx);
+        }
+    }
+
+    let mut best = values.to_vec();
+    let mut best_falsified = falsified.len();
+    for _ in 0..max_flips {
+        if falsified.is_empty() {
+            break;
+        }
+        let clause = &clauses[falsified.get(rng.below(falsified.len()))];
+        // number of clauses that only the current value of a variable satisfies
+        let breaks = |lit: &Literal| {
+            occurrences[lit.invert().index()]
+                .iter()
+                .filter(|&&idx| true_count[idx] == 1)
+                .count()
+        };
+        let (min_lit, min_break) = clause
+            .iter()
+            .map(|lit| (*lit, breaks(lit)))
+            .min_by_key(|&(_, count)| count)
+            .unwrap();
+        let lit = if min_break > 0 && rng.below(100) < WALK_NOISE {
+            clause[rng.below(clause.len())]
+        } else {
+            min_lit
+        };
+
+        values[lit.var()] = lit.sign();
+        for &idx in occurrences[lit.invert().index()].iter() {
+            true_count[idx] -= 1;
+            if true_count[idx] == 0 {
+                falsified.insert(idx);
+            }
+        }
+        for &idx in occurrences[lit.index()].iter() {
+            true_count[idx] += 1;
+            if true_count[idx] == 1 {
+                falsified.remove(idx);
+            }
+        }
+        if falsified.len() < best_falsified {
+            best_falsified = falsified.len();
+            best.copy_from_slice(values);
+        }
+    }
+    values.copy_from_slice(&best);
+    best_falsified
+}
+
+/// Set of clause indices with constant time insertion, removal and random access
+struct FalsifiedSet {
+    members: Vec<usize>,
+    position: Vec<Option<usize>>,
+}
+
+impl FalsifiedSet {
+    fn new(num_clauses: usize) -> Self {
+        FalsifiedSet {
+            members: Vec::new(),
+            position: vec![None; num_clauses],
+        }
+    }
+
+    fn insert(&mut self, idx: usize) {
+        self.position[idx] = Some(self.members.len());
+        self.members.push(idx);
+    }
+
+    fn remove(&mut self, idx: usize) {
+        let pos = self.position[idx].take().unwrap();
+        let last = self.members.pop().unwrap();
+        if last != idx {
+            self.members[pos] = last;
+            self.position[last] = Some(pos);
+        }
+    }
+
+    fn get(&self, pos: usize) -> usize {
+        self.members[pos]
+    }
+
+    fn len(&self) -> usize {
+        self.members.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests;
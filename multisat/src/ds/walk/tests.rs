@@ -0,0 +1,28 @@
+use super::*;
+use crate::test_util::clauses;
+
+fn falsified(clauses: &[Vec<Literal>], values: &[bool]) -> usize {
+    clauses
+        .iter()
+        .filter(|clause| !clause.iter().any(|lit| values[lit.var()] == lit.sign()))
+        .count()
+}
+
+#[test]
+fn walk_finds_model_of_easy_formula() {
+    let formula = clauses(&[&[1, 2], &[-1, 3], &[-2, -3], &[-3, 4], &[-4, -1]]);
+    let mut values = vec![true; 5];
+    let mut rng = Rng::new(7);
+    assert_eq!(walk(&formula, &mut values, 1000, &mut rng), 0);
+    assert_eq!(falsified(&formula, &values), 0);
+}
+
+#[test]
+fn walk_returns_best_assignment_of_unsat_formula() {
+    let formula = clauses(&[&[1], &[-1], &[2, 1], &[-2, 1]]);
+    let mut values = vec![false; 3];
+    let mut rng = Rng::new(3);
+    let best = walk(&formula, &mut values, 100, &mut rng);
+    assert_eq!(best, 1);
+    assert_eq!(falsified(&formula, &values), best);
+}
@@ -0,0 +1,297 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::process::exit;
+
+use crate::ds::reconstruct::ReconstructionStack;
+use crate::ds::*;
+use crate::parse::parse_cnf;
+use crate::verify::read_model;
+
+/// What is needed to turn a model of the simplified formula into one of the
+/// original formula
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReconstructionMap {
+    pub original_variables: usize,
+    /// Original variable of each simplified variable, variable `v` is at `v - 1`
+    pub variables: Vec<LiteralSize>,
+    /// Literals preprocessing assigned, over the original variables
+    pub fixed: Vec<Literal>,
+    /// Clauses removed by eliminations, over the original variables
+    pub reconstruction: ReconstructionStack,
+}
+
+/// The irredundant clauses left after preprocessing, over the active variables
+/// renumbered from 1
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimplifiedFormula {
+    pub clauses: Vec<Vec<Literal>>,
+    pub map: ReconstructionMap,
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+impl SimplifiedFormula {
+    /// Extracts the simplified formula from a state at level 0, e.g. right after
+    /// `preprocess`. Learned clauses are left out, satisfied clauses are dropped
+    /// and so are false literals. An UNSAT state gives the empty clause.
+    pub fn from_state(solver_state: &SolverState) -> Self {
+        assert!(solver_state.level == 0);
+        let mut map = ReconstructionMap {
+            original_variables: solver_state.num_variables,
+            variables: Vec::new(),
+            fixed: Vec::new(),
+            reconstruction: solver_state.reconstruction.clone(),
+        };
+        if solver_state.is_unsat_at_root() {
+            return SimplifiedFormula {
+                clauses: vec![Vec::new()],
+                map,
+            };
+        }
+        for var in 1..=solver_state.num_variables {
+            if let Some(info) = solver_state.assig.get(&var) {
+                map.fixed.push(Literal::new(var, info.litsign));
+            }
+        }
+
+        // simplified variable of each original one, 0 until it is seen
+        let mut renumbered: Vec<LiteralSize> = vec![0; solver_state.num_variables + 1];
+        let clause_db = &solver_state.clause_db;
+        let mut clauses: Vec<Vec<Literal>> = Vec::new();
+        for cref in clause_db.iter() {
+            let lits = clause_db.lits(cref);
+            if clause_db.is_learned(cref) || clause_satisfied(lits, &solver_state.assig) {
+                continue;
+            }
+            let mut clause: Vec<Literal> = Vec::with_capacity(lits.len());
+            for lit in lits.iter() {
+                if !literal_unassigned(lit, &solver_state.assig) {
+                    continue;
+                }
+                debug_assert!(!solver_state.assig.is_eliminated(lit.var()));
+                if renumbered[lit.var()] == 0 {
+                    map.variables.push(lit.var());
+                    renumbered[lit.var()] = map.variables.len();
+                }
+                clause.push(Literal::new(renumbered[lit.var()], lit.sign()));
+            }
+            clauses.push(clause);
+        }
+        SimplifiedFormula { clauses, map }
+    }
+
+    pub fn num_variables(&self) -> usize {
+        self.map.variables.len()
+    }
+
+    pub fn write_dimacs(&self, out: &mut impl Write) -> io::Result<()> {
+        writeln!(
+            out,
+            "c simplified by multisat from {} variables",
+            self.map.original_variables
+        )?;
+        writeln!(out, "p cnf {} {}", self.num_variables(), self.clauses.len())?;
+        for clause in self.clauses.iter() {
+            for lit in clause.iter() {
+                write!(out, "{} ", lit)?;
+            }
+            writeln!(out, "0")?;
+        }
+        Ok(())
+    }
+}
+
+impl ReconstructionMap {
+    /// Writes the map as lines `m <simplified> <original>` for the variables,
+    /// `f <literal>` for the fixed literals and `e <witness> <clause> 0` for the
+    /// removed clauses in the order they were removed
+    pub fn write(&self, out: &mut impl Write) -> io::Result<()> {
+        writeln!(out, "c multisat reconstruction map")?;
+        writeln!(
+            out,
+            "p map {} {}",
+            self.original_variables,
+            self.variables.len()
+        )?;
+        for (idx, var) in self.variables.iter().enumerate() {
+            writeln!(out, "m {} {}", idx + 1, var)?;
+        }
+        for lit in self.fixed.iter() {
+            writeln!(out, "f {}", lit)?;
+        }
+        for entry in self.reconstruction.entries() {
+            write!(out, "e {}", entry.witness)?;
+            for lit in entry.clause.iter() {
+                write!(out, " {}", lit)?;
+            }
+            writeln!(out, " 0")?;
+        }
+        Ok(())
+    }
+
+    /// Reads a map written by `write`
+    pub fn read(reader: impl BufRead) -> io::Result<Self> {
+        let mut map: Option<ReconstructionMap> = None;
+        for line in reader.lines() {
+            let line = line?;
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            if parts.is_empty() || parts[0] == "c" {
+                continue;
+            }
+            // counts and literals fit an i32, except i32::MIN which has no negation
+            let numbers = parts
+                .iter()
+                .skip(if parts[0] == "p" { 2 } else { 1 })
+                .map(|part| part.parse::<i32>().ok().filter(|&n| n != i32::MIN))
+                .collect::<Option<Vec<i32>>>()
+                .ok_or_else(|| invalid_data(format!("Invalid map line {}", line)))?;
+            match (parts[0], map.as_mut()) {
+                ("p", None)
+                    if parts.get(1) == Some(&"map")
+                        && numbers.len() == 2
+                        && numbers.iter().all(|&n| n >= 0) =>
+                {
+                    map = Some(ReconstructionMap {
+                        original_variables: numbers[0] as usize,
+                        variables: Vec::new(),
+                        fixed: Vec::new(),
+                        reconstruction: ReconstructionStack::new(),
+                    });
+                }
+                ("m", Some(map))
+                    if numbers.len() == 2
+                        && numbers[0] > 0
+                        && numbers[0] as usize == map.variables.len() + 1
+                        && numbers[1] > 0 =>
+                {
+                    map.variables.push(numbers[1] as LiteralSize);
+                }
+                ("f", Some(map)) if numbers.len() == 1 && numbers[0] != 0 => {
+                    map.fixed.push(Literal::from(numbers[0]));
+                }
+                ("e", Some(map))
+                    if numbers.len() >= 3
+                        && numbers.last() == Some(&0)
+                        && !numbers[..numbers.len() - 1].contains(&0)
+                        && numbers[1..numbers.len() - 1].contains(&numbers[0]) =>
+                {
+                    let clause: Vec<Literal> = numbers[1..numbers.len() - 1]
+                        .iter()
+                        .map(|&n| Literal::from(n))
+                        .collect();
+                    map.reconstruction.push(Literal::from(numbers[0]), &clause);
+                }
+                _ => return Err(invalid_data(format!("Invalid map line {}", line))),
+            }
+        }
+        let map = map.ok_or_else(|| invalid_data("No map header found".to_string()))?;
+        let in_range = |var: LiteralSize| var > 0 && var <= map.original_variables;
+        if !map.variables.iter().all(|&var| in_range(var))
+            || !map.fixed.iter().all(|lit| in_range(lit.var()))
+            || !map
+                .reconstruction
+                .entries()
+                .iter()
+                .all(|entry| entry.clause.iter().all(|lit| in_range(lit.var())))
+        {
+            return Err(invalid_data(
+                "Map refers to a variable out of range".to_string(),
+            ));
+        }
+        Ok(map)
+    }
+
+    /// Turns a model of the simplified formula into one of the original
+    /// formula. Simplified variables missing from `model` are taken as false.
+    pub fn lift(&self, model: &[i32]) -> io::Result<Vec<i32>> {
+        let mut values: Vec<bool> = vec![false; self.original_variables + 1];
+        for lit in self.fixed.iter() {
+            values[lit.var()] = lit.sign();
+        }
+        for &n in model.iter() {
+            let lit = Literal::from(n);
+            match self.variables.get(lit.var().wrapping_sub(1)) {
+                Some(&var) => values[var] = lit.sign(),
+                None => {
+                    return Err(invalid_data(format!(
+                        "Literal {} of the model is not a simplified variable",
+                        n
+                    )))
+                }
+            }
+        }
+        self.reconstruction.extend(&mut values);
+        Ok((1..=self.original_variables)
+            .map(|var| var as i32 * if values[var] { 1 } else { -1 })
+            .collect())
+    }
+}
+
+/// Where the map of a simplified formula written to `out_file` goes
+pub fn map_file(out_file: &str) -> String {
+    format!("{}.map", out_file)
+}
+
+fn write_file(file: &str, write: impl FnOnce(&mut BufWriter<File>) -> io::Result<()>) {
+    let written = File::create(file).and_then(|f| {
+        let mut out = BufWriter::new(f);
+        write(&mut out)?;
+        out.flush()
+    });
+    if let Err(e) = written {
+        println!("c Error: {}: {}", file, e);
+        exit(1);
+    }
+}
+
+/// Preprocesses `formula_file` and writes the simplified formula to
+/// `out_file` and its map next to it
+pub fn run_simplify_only(formula_file: String, out_file: String) {
+    let parsed_out = match parse_cnf(&formula_file) {
+        Ok(p) => p,
+        Err(e) => {
+            println!("c Error: {}", e);
+            exit(1);
+        }
+    };
+    let mut solver_state = SolverState::from_parsed_out(parsed_out);
+    solver_state.preprocess();
+    let simplified = SimplifiedFormula::from_state(&solver_state);
+    write_file(&out_file, |out| simplified.write_dimacs(out));
+    let map_file = map_file(&out_file);
+    write_file(&map_file, |out| simplified.map.write(out));
+    println!(
+        "c simplified {} variables to {} variables {} clauses",
+        simplified.map.original_variables,
+        simplified.num_variables(),
+        simplified.clauses.len()
+    );
+    println!("c wrote {} and {}", out_file, map_file);
+}
+
+/// Prints the model in `model_file`, of a formula written by
+/// `run_simplify_only`, lifted to the original variables
+pub fn run_lift(map_file: String, model_file: String) {
+    let lifted = File::open(&map_file)
+        .and_then(|f| ReconstructionMap::read(BufReader::new(f)))
+        .and_then(|map| {
+            let model = File::open(&model_file).and_then(read_model)?;
+            map.lift(&model)
+        });
+    match lifted {
+        Ok(model) => {
+            println!("s SATISFIABLE");
+            let lits: Vec<String> = model.iter().map(|n| n.to_string()).collect();
+            println!("v {} 0", lits.join(" "));
+        }
+        Err(e) => {
+            println!("c Error: {}", e);
+            exit(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
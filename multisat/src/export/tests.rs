@@ -0,0 +1,129 @@
+use super::*;
+use crate::differential::{brute_force, models, Instance};
+use crate::test_util::{lits, satisfies, state};
+
+/// `clauses` as an instance for the truth table
+fn instance(num_variables: usize, clauses: &[Vec<Literal>]) -> Instance {
+    Instance {
+        num_variables,
+        clauses: clauses
+            .iter()
+            .map(|clause| clause.iter().map(|lit| lit.to_dimacs()).collect())
+            .collect(),
+    }
+}
+
+/// Random 3-SAT with a few binary clauses, from a fixed seed
+fn random_formula(seed: u64, num_variables: usize, num_clauses: usize) -> Vec<Vec<Literal>> {
+    let mut rng = Rng::new(seed);
+    (0..num_clauses)
+        .map(|idx| {
+            let len = if idx % 4 == 0 { 2 } else { 3 };
+            let mut clause: Vec<Literal> = Vec::new();
+            while clause.len() < len {
+                let lit = Literal::new(rng.below(num_variables) + 1, rng.coin());
+                if clause.iter().all(|other| other.var() != lit.var()) {
+                    clause.push(lit);
+                }
+            }
+            clause
+        })
+        .collect()
+}
+
+#[test]
+fn fixed_and_unused_variables_are_dropped() {
+    let original = vec![lits(&[1]), lits(&[-1, 3, 5]), lits(&[-3, -5])];
+    let mut s = state(5, &original);
+    assert!(matches!(s.preprocess_keep_models(), FormulaPreprocess::Ok));
+    let simplified = SimplifiedFormula::from_state(&s);
+    assert_eq!(simplified.map.variables, vec![3, 5]);
+    assert_eq!(simplified.map.fixed, lits(&[1]));
+    let mut clauses: Vec<Vec<i32>> = simplified
+        .clauses
+        .iter()
+        .map(|clause| {
+            let mut clause: Vec<i32> = clause.iter().map(|lit| lit.to_dimacs()).collect();
+            clause.sort();
+            clause
+        })
+        .collect();
+    clauses.sort();
+    assert_eq!(clauses, vec![vec![-2, -1], vec![1, 2]]);
+
+    let mut dimacs: Vec<u8> = Vec::new();
+    simplified.write_dimacs(&mut dimacs).unwrap();
+    assert!(String::from_utf8(dimacs).unwrap().contains("p cnf 2 2\n"));
+}
+
+#[test]
+fn unsat_is_the_empty_clause() {
+    let mut s = state(2, &[lits(&[1, 2]), lits(&[-1, 2]), lits(&[-2])]);
+    assert!(matches!(s.preprocess(), FormulaPreprocess::TrivialUNSAT));
+    let simplified = SimplifiedFormula::from_state(&s);
+    let mut dimacs: Vec<u8> = Vec::new();
+    simplified.write_dimacs(&mut dimacs).unwrap();
+    assert!(String::from_utf8(dimacs)
+        .unwrap()
+        .ends_with("p cnf 0 1\n0\n"));
+}
+
+#[test]
+fn map_survives_writing_and_reading() {
+    let original = random_formula(7, 10, 30);
+    let mut s = state(10, &original);
+    s.preprocess();
+    let simplified = SimplifiedFormula::from_state(&s);
+    assert!(!simplified.map.reconstruction.is_empty());
+    let mut written: Vec<u8> = Vec::new();
+    simplified.map.write(&mut written).unwrap();
+    let read = ReconstructionMap::read(written.as_slice()).unwrap();
+    assert_eq!(read, simplified.map);
+}
+
+#[test]
+fn malformed_maps_are_rejected() {
+    // before the header, out of range, not numbered in order, witness missing,
+    // negative or overflowing numbers, 0 as witness or inside a clause
+    for map in [
+        "m 1 1\np map 2 1\n",
+        "p map 2 1\nm 1 3\n",
+        "p map 2 2\nm 2 1\n",
+        "p map 2 0\ne 1 -2 0\n",
+        "p map 2 0\nf x\n",
+        "p map 3 -1\n",
+        "p map 2 1\nm 1 -1\n",
+        "p map 2 0\nf 4294967297\n",
+        "p map 2 0\nf -2147483648\n",
+        "p map 2 0\nf 0\n",
+        "p map 2 0\ne 0 1 0\n",
+        "p map 2 0\ne 1 0 1 0\n",
+    ] {
+        assert!(ReconstructionMap::read(map.as_bytes()).is_err(), "{}", map);
+    }
+    let map = ReconstructionMap::read("p map 3 1\nm 1 2\n".as_bytes()).unwrap();
+    assert!(map.lift(&[2]).is_err());
+    assert_eq!(map.lift(&[-1]).unwrap(), vec![-1, -2, -3]);
+}
+
+#[test]
+fn lifted_models_satisfy_original() {
+    for seed in 1..40 {
+        let original = random_formula(seed, 10, 38);
+        let mut s = state(10, &original);
+        s.preprocess();
+        let simplified = SimplifiedFormula::from_state(&s);
+        let simplified_models: Vec<Vec<i32>> =
+            models(&instance(simplified.num_variables(), &simplified.clauses)).collect();
+        assert_eq!(
+            simplified_models.is_empty(),
+            brute_force(&instance(10, &original)).is_none(),
+            "seed {}",
+            seed
+        );
+        for model in simplified_models {
+            let lifted = simplified.map.lift(&model).unwrap();
+            assert!(satisfies(&lifted, &original), "seed {}", seed);
+        }
+    }
+}
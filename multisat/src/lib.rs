@@ -0,0 +1,89 @@
+use crate::ds::*;
+
+pub mod backbone;
+pub mod bench;
+pub mod delta;
+pub mod differential;
+pub mod ds;
+pub mod export;
+pub mod parse;
+pub mod verify;
+
+#[cfg(test)]
+pub(crate) mod test_util;
+
+#[derive(Debug, PartialEq)]
+pub enum CNFStatus {
+    SAT { model: Vec<i32> },
+    UNSAT,
+}
+fn unit_prop_sat(solver_state: &mut SolverState) -> bool {
+    loop {
+        // println!("UP level: {}", solver_state.level);
+        match solver_state.unit_prop() {
+            FormulaUnitProp::Ok => {
+                return true;
+            }
+            FormulaUnitProp::Conflict { conflict_cause } => {
+                let conflict_res = solver_state.analyze_conflict_backtrack(conflict_cause);
+                match conflict_res {
+                    ConflictAnalysisResult::UNSAT => return false,
+                    ConflictAnalysisResult::Backtrack { .. } => {
+                        // println!("CONFLICT");
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Searches for a model in which every assumption holds. Assumptions are decided
+/// before any other variable, so UNSAT only means the formula is unsatisfiable
+/// together with them. Learned clauses are kept, so the state can be reused for
+/// further calls.
+pub fn solve_with_assumptions(solver_state: &mut SolverState, assumptions: &[Literal]) -> CNFStatus {
+    if solver_state.is_unsat_at_root() {
+        return CNFStatus::UNSAT;
+    }
+    solver_state.backtrack_to_root();
+    loop {
+        if assumptions
+            .iter()
+            .any(|lit| literal_falsified(lit, &solver_state.assig))
+        {
+            return CNFStatus::UNSAT;
+        }
+        if solver_state.all_assigned() {
+            break;
+        }
+        // println!("Num clauses is {}", solver_state.num_clauses());
+        debug_assert!(solver_state.check_watch_invariant());
+        if solver_state.restart_search() {
+            solver_state.inprocess_if_needed();
+            // assignments kept below the restart level may still have to be
+            // propagated, and the passes may have assigned or eliminated the
+            // remaining variables
+            if solver_state.is_unsat_at_root() || !unit_prop_sat(solver_state) {
+                return CNFStatus::UNSAT;
+            }
+            continue;
+        }
+        solver_state.reduce_db_if_needed();
+        solver_state.rephase_if_needed();
+        let lit = match assumptions
+            .iter()
+            .find(|lit| literal_unassigned(lit, &solver_state.assig))
+        {
+            Some(&assumption) => assumption,
+            None => solver_state.pick_var(),
+        };
+        let recent_dec: Decision = Decision::make_choice(lit);
+        solver_state.add_decision(&recent_dec);
+        if !unit_prop_sat(solver_state) {
+            return CNFStatus::UNSAT;
+        }
+    }
+    CNFStatus::SAT {
+        model: solver_state.get_model(),
+    }
+}
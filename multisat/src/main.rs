@@ -1,62 +1,59 @@
-use rustc_hash::FxHashMap;
+use rustc_hash::{FxHashMap, FxHashSet};
 
-use crate::ds::*;
-use crate::parse::*;
+use multisat::ds::heuristic::HeuristicKind;
+use multisat::ds::restart::RestartPolicy;
+use multisat::ds::*;
+use multisat::parse::*;
+use multisat::{backbone, export, solve_with_assumptions, CNFStatus};
 use std::env;
 use std::path::Path;
 use std::process::exit;
 
-pub mod ds;
-pub mod parse;
-
-#[derive(Debug, PartialEq)]
-pub enum CNFStatus {
-    SAT { model: Vec<i32> },
-    UNSAT,
-}
-fn unit_prop_sat(solver_state: &mut SolverState, recent_dec: &Decision) -> bool {
-    let mut cur_dec = recent_dec.clone();
-    loop {
-        // println!("UP {:?} level: {}", cur_dec,solver_state.level);
-        match solver_state.unit_prop(&cur_dec) {
-            FormulaUnitProp::Ok => {
-                return true;
-            }
-            FormulaUnitProp::Conflict { conflict_cause_idx } => {
-                let conflict_res = solver_state.analyze_conflict_backtrack(conflict_cause_idx);
-                match conflict_res {
-                    ConflictAnalysisResult::UNSAT => return false,
-                    ConflictAnalysisResult::Backtrack { decision } => {
-                        // println!("CONFLICT");
-                        cur_dec = decision;
-                    }
-                }
-            }
-        }
-    }
-}
-
 fn solver(solver_state: &mut SolverState) -> CNFStatus {
     println!(
         "Have {} vars {} clauses Assigned {} vars in preprocessing",
         solver_state.num_variables,
-        solver_state.clauses.len(),
+        solver_state.num_clauses(),
         solver_state.assigments_len()
     );
-    while solver_state.assigments_len() < solver_state.num_variables {
-        // println!("Num clauses is {}", solver_state.clauses.len());
-        debug_assert!(solver_state.check_watch_invariant());
-        solver_state.restart_search();
-        let recent_dec: Decision = Decision::make_choice(solver_state.pick_var());
-        solver_state.add_decision(&recent_dec);
-        if !unit_prop_sat(solver_state, &recent_dec) {
-            return CNFStatus::UNSAT;
-        }
-    }
-    CNFStatus::SAT {
-        model: solver_state.get_model(),
+    let res = solve_with_assumptions(solver_state, &[]);
+    let stats = &solver_state.minimize_stats;
+    println!(
+        "c minimization: {} learnt literals, removed {} by shrinking {} recursively {} by binary implications",
+        stats.learnt_literals, stats.shrunk, stats.recursive, stats.binary
+    );
+    println!("c mode switches: {}", solver_state.mode_switches);
+    println!("c rephases: {}", solver_state.rephases);
+    let simplified = &solver_state.simplify_stats;
+    println!(
+        "c simplification: eliminated {} substituted {} variables, subsumed {} strengthened {} clauses",
+        simplified.eliminated, simplified.substituted, simplified.subsumed, simplified.strengthened
+    );
+    println!(
+        "c blocked clauses: {} covered clauses: {} vivified clauses: {}",
+        simplified.blocked, simplified.covered, simplified.vivified
+    );
+    println!(
+        "c probing: {} failed literals {} common implications {} hyper-binary resolvents",
+        simplified.failed_literals, simplified.common_implied, simplified.hyper_binary
+    );
+    let runs: Vec<String> = solver_state
+        .inprocessing
+        .runs()
+        .map(|(pass, runs)| format!("{} {}", pass.name(), runs))
+        .collect();
+    println!("c inprocessing: {}", runs.join(" "));
+    let restarts = &solver_state.restarts;
+    println!(
+        "c restarts: {} blocked {} reused trail {}",
+        restarts.restarts, restarts.blocked, restarts.reused_trails
+    );
+    if solver_state.options.chrono_threshold.is_some() {
+        println!("c chronological backtracks: {}", solver_state.chrono_backtracks);
     }
+    res
 }
+
 pub fn print_result(formula_file: String, res: CNFStatus, time: f32) {
     let mut res_dict: FxHashMap<String, String> = FxHashMap::default();
     res_dict.insert(
@@ -107,35 +104,46 @@ pub fn check_result(solver_state: &SolverState, res: &CNFStatus) {
     println!("Checking result");
 
     if let CNFStatus::SAT { model }  = res {
-            //Check that assignment equal to model
+            //Check that assignment equal to model, eliminated variables are
+            //only set in the model and witnesses of removed clauses may be
+            //flipped by the reconstruction
+            let witnesses: FxHashSet<LiteralSize> = solver_state
+                .reconstruction
+                .entries()
+                .iter()
+                .map(|entry| entry.witness.var())
+                .collect();
             for &n in model.iter() {
                 let lit = Literal::from(n);
-                let assig = solver_state.assig.get(&lit.var).unwrap();
-                if assig.litsign != n.is_positive() {
-                    println!("Error: assignment {:?} not equal to model {:?}", assig, lit);
-                    exit(1);
+                if witnesses.contains(&lit.var()) {
+                    continue;
+                }
+                if let Some(assig) = solver_state.assig.get(&lit.var()) {
+                    if assig.litsign != n.is_positive() {
+                        println!("Error: assignment {:?} not equal to model {:?}", assig, lit);
+                        exit(1);
+                    }
                 }
             }
 
-            for clause in solver_state.clauses.iter() {
+            for clause in solver_state.clauses() {
                 let satisfied = clause
-                    .literals
                     .iter()
-                    .any(|&lit| lit.sign == model[lit.var - 1].is_positive());
+                    .any(|&lit| lit.sign() == model[lit.var() - 1].is_positive());
 
                 if !satisfied {
                     let mut corresponding_assig: Vec<Literal> = Vec::new();
-                    for &lit in clause.literals.iter() {
-                        corresponding_assig.push(Literal {
-                            var: lit.var,
-                            sign: solver_state.assig.get(&lit.var).unwrap().litsign,
-                        });
+                    for &lit in clause.iter() {
+                        corresponding_assig.push(Literal::new(
+                            lit.var(),
+                            model[lit.var() - 1].is_positive(),
+                        ));
                     }
                     println!(
                         "Error: clause {:?} not satisfied corrsp assig {:?}",
                         clause, corresponding_assig
                     );
-                    assert!(false);
+                    panic!("model does not satisfy the formula");
                 }
             }
             println!("c SAT ALL GOOD");
@@ -143,7 +151,7 @@ pub fn check_result(solver_state: &SolverState, res: &CNFStatus) {
     }
 }
 
-fn run_solver(formula_file : String) -> (f32, CNFStatus){
+fn run_solver(formula_file : String, options: SolverOptions) -> (f32, CNFStatus){
     let start = std::time::Instant::now();
     let parsed_out = match parse_cnf(&formula_file) {
         Ok(p) => p,
@@ -153,6 +161,7 @@ fn run_solver(formula_file : String) -> (f32, CNFStatus){
         }
     };
     let mut solver_state = SolverState::from_parsed_out(parsed_out);
+    solver_state.set_options(options);
     match solver_state.preprocess() {
         FormulaPreprocess::Ok => {}
         FormulaPreprocess::TrivialUNSAT => {
@@ -170,12 +179,55 @@ fn run_solver(formula_file : String) -> (f32, CNFStatus){
     total = (total * 100.0).round() / 100.0;
      (total,res)
 }
+fn usage() -> ! {
+    println!(
+        "usage: multisat <formula.cnf> [--chrono [<threshold>]] [--heuristic {}] [--restart {}] [--reuse-trail]",
+        HeuristicKind::NAMES.join("|"),
+        RestartPolicy::NAMES.join("|")
+    );
+    println!("       multisat <formula.cnf> --simplify-only <out.cnf>");
+    println!("       multisat backbone <formula.cnf> [--chunk <size>]");
+    println!("       multisat lift <out.cnf.map> <model>");
+    exit(1);
+}
+
 fn main() {
     // //get current time
     // println!("Size of option struct is {}", std::mem::size_of::<Option<AssigInfo>>());
     let args: Vec<String> = env::args().collect();
-    let formula_file = args[1].clone();
-    // let formula_file = "../input/C168_128.cnf".to_string();
-    let (total,res) = run_solver(formula_file.clone());
-    print_result(formula_file, res, total);
+    match args.get(1).map(String::as_str) {
+        Some("backbone") => {
+            let formula_file = args.get(2).cloned().unwrap_or_else(|| usage());
+            let chunk_size = match args.get(3).map(String::as_str) {
+                None => backbone::DEFAULT_CHUNK_SIZE,
+                Some("--chunk") => args
+                    .get(4)
+                    .and_then(|n| n.parse().ok())
+                    .filter(|&n| n > 0)
+                    .unwrap_or_else(|| usage()),
+                Some(_) => usage(),
+            };
+            backbone::run_backbone(formula_file, chunk_size);
+        }
+        Some("lift") => {
+            if args.len() != 4 {
+                usage();
+            }
+            export::run_lift(args[2].clone(), args[3].clone());
+        }
+        Some(formula_file) if args.get(2).map(String::as_str) == Some("--simplify-only") => {
+            if args.len() != 4 {
+                usage();
+            }
+            export::run_simplify_only(formula_file.to_string(), args[3].clone());
+        }
+        Some(formula_file) => {
+            let formula_file = formula_file.to_string();
+            // let formula_file = "../input/C168_128.cnf".to_string();
+            let options = SolverOptions::from_args(&args[2..]).unwrap_or_else(|| usage());
+            let (total, res) = run_solver(formula_file.clone(), options);
+            print_result(formula_file, res, total);
+        }
+        None => usage(),
+    }
 }
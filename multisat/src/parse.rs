@@ -10,6 +10,8 @@ pub fn parse_cnf(filename: &str) -> io::Result<ParsedOut> {
 
     let mut num_clauses: usize = 0;
     let mut num_variables: usize = 0;
+    // a formula may have no variables, e.g. a simplified one
+    let mut header_seen = false;
     let mut clause_vec: Vec<Vec<Literal>> = Vec::new();
 
     for line in reader.lines() {
@@ -24,7 +26,8 @@ pub fn parse_cnf(filename: &str) -> io::Result<ParsedOut> {
             let parts: Vec<&str> = cleaned.split_whitespace().collect();
             num_variables = parts[2].parse().unwrap();
             num_clauses = parts[3].parse().unwrap();
-        } else if num_clauses != 0 {
+            header_seen = true;
+        } else if header_seen {
             let lit_vec = cleaned
                 .split_whitespace()
                 .map(|s| s.parse::<i32>().unwrap())
@@ -40,7 +43,7 @@ pub fn parse_cnf(filename: &str) -> io::Result<ParsedOut> {
             ));
         }
     }
-    if num_variables == 0 {
+    if !header_seen {
         Err(io::Error::new(
             io::ErrorKind::InvalidData,
             "No CNF header found",
@@ -55,5 +58,39 @@ pub fn parse_cnf(filename: &str) -> io::Result<ParsedOut> {
     }
 }
 
+/// Reads a model in the competition output format: the literals of the `v`
+/// lines up to the terminating 0. Other lines, e.g. comments and the `s` line,
+/// are skipped, but a solver that reported UNSATISFIABLE has no model.
+pub fn parse_model(reader: impl BufRead) -> io::Result<Vec<i32>> {
+    let mut model: Vec<i32> = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        let cleaned = line.trim();
+        if cleaned.starts_with("s UNSATISFIABLE") {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "No model, the formula is UNSATISFIABLE",
+            ));
+        }
+        let values = match cleaned.strip_prefix('v') {
+            Some(values) => values,
+            None => continue,
+        };
+        for value in values.split_whitespace() {
+            let lit: i32 = value.parse().map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Invalid literal {} in model", value),
+                )
+            })?;
+            if lit == 0 {
+                return Ok(model);
+            }
+            model.push(lit);
+        }
+    }
+    Ok(model)
+}
+
 #[cfg(test)]
 mod tests;
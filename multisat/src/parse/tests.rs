@@ -1,9 +1,9 @@
 use std::fs;
 use std::fs::File;
 use std::io::Write;
-use crate::parse::ParseOpt;
+use crate::ds::{FormulaPreprocess, SolverState};
 
-use super::super::parse_cnf;
+use super::{parse_cnf, parse_model};
 
 // Helper function to create a test CNF file
 fn create_test_cnf_file(content: &str, filename: &str) {
@@ -21,7 +21,7 @@ fn test_successful_parsing() {
     let filename = "test_cnf_success.cnf";
     create_test_cnf_file("p cnf 3 2\nc comment line\n1 -2 0\n-3 1 0", filename);
 
-    let solver_state = parse_cnf(filename).unwrap().get_solver_state();
+    let solver_state = SolverState::from_parsed_out(parse_cnf(filename).unwrap());
     cleanup_test_file(filename); // Clean up after test
 
     assert_eq!(solver_state.num_variables, 3);
@@ -66,7 +66,7 @@ fn test_parse_unit_clauses() {
     let filename = "test_cnf_unit_clauses.cnf";
     create_test_cnf_file("p cnf 3 2\n1 0\n-2 0", filename);
 
-    let solver_state = parse_cnf(filename).unwrap().get_solver_state();
+    let solver_state = SolverState::from_parsed_out(parse_cnf(filename).unwrap());
     cleanup_test_file(filename); // Clean up after test
 
     assert_eq!(solver_state.num_variables, 3);
@@ -74,10 +74,35 @@ fn test_parse_unit_clauses() {
 
 
 #[test]
-fn test_opposite_unit_clauses() { 
+fn test_opposite_unit_clauses() {
     let filename = "test_cnf_opposite_unit_clauses.cnf";
     create_test_cnf_file("p cnf 1 2\n1 0\n-1 0", filename);
 
-    assert_eq!(parse_cnf(filename).unwrap(),ParseOpt::TrivialUNSAT);
-    cleanup_test_file(filename); 
-}
\ No newline at end of file
+    let mut solver_state = SolverState::from_parsed_out(parse_cnf(filename).unwrap());
+    cleanup_test_file(filename);
+
+    assert!(matches!(
+        solver_state.preprocess(),
+        FormulaPreprocess::TrivialUNSAT
+    ));
+}
+
+#[test]
+fn test_parse_model() {
+    let output = "c comment\ns SATISFIABLE\nv 1 -2\nv 3 0\n";
+    assert_eq!(parse_model(output.as_bytes()).unwrap(), vec![1, -2, 3]);
+    assert!(parse_model("s UNSATISFIABLE\n".as_bytes()).is_err());
+    assert!(parse_model("v 1 x 0\n".as_bytes()).is_err());
+}
+
+#[test]
+fn test_parse_empty_formula() {
+    let filename = "test_cnf_empty_formula.cnf";
+    create_test_cnf_file("p cnf 0 0\n", filename);
+
+    let parsed_out = parse_cnf(filename).unwrap();
+    cleanup_test_file(filename);
+
+    assert_eq!(parsed_out.num_variables, 0);
+    assert!(parsed_out.clauses.is_empty());
+}
@@ -0,0 +1,49 @@
+//! Fixtures shared by the unit tests
+
+use crate::ds::*;
+use crate::{solve_with_assumptions, CNFStatus};
+
+/// Literals from DIMACS numbers
+pub fn lits(raw: &[i32]) -> Vec<Literal> {
+    raw.iter().map(|&n| Literal::from(n)).collect()
+}
+
+pub fn clauses(raw: &[&[i32]]) -> Vec<Vec<Literal>> {
+    raw.iter().map(|clause| lits(clause)).collect()
+}
+
+/// Whether `model`, one DIMACS literal per variable in order, satisfies every
+/// clause
+pub fn satisfies(model: &[i32], clauses: &[Vec<Literal>]) -> bool {
+    clauses.iter().all(|clause| {
+        clause
+            .iter()
+            .any(|lit| model[lit.var() - 1] == lit.to_dimacs())
+    })
+}
+
+/// Solves `s`, which has to be satisfiable, and checks the model against
+/// `original`, the clauses before any simplification
+pub fn assert_reconstructs(s: &mut SolverState, original: &[Vec<Literal>]) {
+    match solve_with_assumptions(s, &[]) {
+        CNFStatus::SAT { model } => assert!(satisfies(&model, original)),
+        CNFStatus::UNSAT => panic!("formula is satisfiable"),
+    }
+}
+
+/// Checks that preprocessing `s`, or solving it if that is not enough, finds
+/// it unsatisfiable
+pub fn assert_unsat(s: &mut SolverState) {
+    let unsat = s.preprocess() == FormulaPreprocess::TrivialUNSAT
+        || solve_with_assumptions(s, &[]) == CNFStatus::UNSAT;
+    assert!(unsat);
+}
+
+/// A solver state over `clauses`, before any preprocessing
+pub fn state(num_variables: usize, clauses: &[Vec<Literal>]) -> SolverState {
+    SolverState::from_parsed_out(ParsedOut {
+        num_variables,
+        num_clauses: clauses.len(),
+        clauses: clauses.to_vec(),
+    })
+}
@@ -0,0 +1,253 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+
+use crate::parse::parse_model;
+
+/// A clause as it is written in the input, with the line it starts on
+#[derive(Debug, Clone, PartialEq)]
+pub struct InputClause {
+    pub line: usize,
+    pub lits: Vec<i32>,
+}
+
+/// A DIMACS formula read without any of the solver's data structures, so
+/// checking a model does not depend on what preprocessing did to the clauses
+#[derive(Debug, Clone, PartialEq)]
+pub struct InputFormula {
+    pub num_variables: usize,
+    pub clauses: Vec<InputClause>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Verdict {
+    Satisfied,
+    /// The first clause without a true literal
+    Falsified(InputClause),
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Reads a DIMACS formula. Clauses may span lines and end with 0, a `%` line
+/// ends the formula. Literals must be within the header's variables and the
+/// clause count must match it.
+pub fn read_formula(reader: impl BufRead) -> io::Result<InputFormula> {
+    let mut header: Option<(usize, usize)> = None;
+    let mut clauses: Vec<InputClause> = Vec::new();
+    let mut current: Option<InputClause> = None;
+    for (idx, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line_number = idx + 1;
+        let cleaned = line.trim();
+        if cleaned.is_empty() || cleaned.starts_with('c') {
+            continue;
+        }
+        if cleaned.starts_with('%') {
+            break;
+        }
+        if cleaned.starts_with('p') {
+            let parts: Vec<&str> = cleaned.split_whitespace().collect();
+            let counts = match parts[..] {
+                ["p", "cnf", vars, clauses] => vars.parse().ok().zip(clauses.parse().ok()),
+                _ => None,
+            };
+            if header.is_some() || counts.is_none() {
+                return Err(invalid_data(format!(
+                    "Invalid header on line {}",
+                    line_number
+                )));
+            }
+            header = counts;
+            continue;
+        }
+        let (num_variables, _) = header.ok_or_else(|| {
+            invalid_data(format!("Clause before the header on line {}", line_number))
+        })?;
+        for value in cleaned.split_whitespace() {
+            let lit: i32 = value.parse().map_err(|_| {
+                invalid_data(format!("Invalid literal {} on line {}", value, line_number))
+            })?;
+            if lit.unsigned_abs() as usize > num_variables {
+                return Err(invalid_data(format!(
+                    "Literal {} on line {} exceeds the {} variables of the header",
+                    lit, line_number, num_variables
+                )));
+            }
+            let clause = current.get_or_insert_with(|| InputClause {
+                line: line_number,
+                lits: Vec::new(),
+            });
+            if lit == 0 {
+                clauses.push(current.take().unwrap());
+            } else {
+                clause.lits.push(lit);
+            }
+        }
+    }
+    // the 0 of the last clause is sometimes missing
+    clauses.extend(current);
+    let (num_variables, num_clauses) =
+        header.ok_or_else(|| invalid_data("No CNF header found".to_string()))?;
+    if clauses.len() != num_clauses {
+        return Err(invalid_data(format!(
+            "Header announces {} clauses, found {}",
+            num_clauses,
+            clauses.len()
+        )));
+    }
+    Ok(InputFormula {
+        num_variables,
+        clauses,
+    })
+}
+
+/// Reads a JSON string up to its closing quote, `text` starts right after the
+/// opening one. Returns the unescaped string and the text after it.
+fn json_string(text: &str) -> Option<(String, &str)> {
+    let mut value = String::new();
+    let mut chars = text.char_indices();
+    while let Some((idx, c)) = chars.next() {
+        match c {
+            '"' => return Some((value, &text[idx + 1..])),
+            '\\' => match chars.next()?.1 {
+                'n' => value.push('\n'),
+                'r' => value.push('\r'),
+                't' => value.push('\t'),
+                'b' => value.push('\u{8}'),
+                'f' => value.push('\u{c}'),
+                'u' => {
+                    let hex: String = (0..4)
+                        .filter_map(|_| chars.next())
+                        .map(|(_, c)| c)
+                        .collect();
+                    value.push(char::from_u32(u32::from_str_radix(&hex, 16).ok()?)?);
+                }
+                c => value.push(c),
+            },
+            c => value.push(c),
+        }
+    }
+    None
+}
+
+/// The string value of `key` in a flat JSON object of string values, like
+/// the line multisat prints
+pub(crate) fn json_field(json: &str, key: &str) -> Option<String> {
+    let mut rest = json.trim().strip_prefix('{')?;
+    loop {
+        let (name, after) = json_string(rest.trim_start().strip_prefix('"')?)?;
+        let after = after.trim_start().strip_prefix(':')?.trim_start();
+        let (value, after) = json_string(after.strip_prefix('"')?)?;
+        if name == key {
+            return Some(value);
+        }
+        rest = after.trim_start().strip_prefix(',')?;
+    }
+}
+
+/// `value` as a JSON string, with quotes
+pub(crate) fn json_quote(value: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Reads the `"Solution"` of the JSON line multisat prints, pairs of a
+/// variable and `true` or `false`
+fn read_json_model(json: &str) -> io::Result<Vec<i32>> {
+    let field = |key: &str| json_field(json, key);
+    if field("Result").as_deref() == Some("UNSAT") {
+        return Err(invalid_data(
+            "No model, the formula is UNSATISFIABLE".to_string(),
+        ));
+    }
+    let solution =
+        field("Solution").ok_or_else(|| invalid_data("No Solution in JSON".to_string()))?;
+    let parts: Vec<&str> = solution.split_whitespace().collect();
+    parts
+        .chunks(2)
+        .map(|pair| match pair {
+            [var, value @ ("true" | "false")] => var
+                .parse::<i32>()
+                .ok()
+                .filter(|&var| var > 0)
+                .map(|var| if *value == "true" { var } else { -var })
+                .ok_or_else(|| invalid_data(format!("Invalid variable {} in model", var))),
+            _ => Err(invalid_data(format!(
+                "Invalid assignment {:?} in model",
+                pair
+            ))),
+        })
+        .collect()
+}
+
+/// Reads a model either as competition `v` lines or as the JSON line multisat
+/// prints
+pub fn read_model(mut reader: impl Read) -> io::Result<Vec<i32>> {
+    let mut text = String::new();
+    reader.read_to_string(&mut text)?;
+    match text
+        .lines()
+        .rev()
+        .find(|line| line.trim_start().starts_with('{'))
+    {
+        Some(json) => read_json_model(json),
+        None => parse_model(text.as_bytes()),
+    }
+}
+
+/// Checks `model` against every clause of `formula`. A clause none of whose
+/// literals is true counts as falsified, so variables the model leaves out
+/// cannot satisfy anything. A model that assigns a variable twice or one the
+/// formula does not have is an error.
+pub fn check_model(formula: &InputFormula, model: &[i32]) -> io::Result<Verdict> {
+    let mut values: Vec<Option<bool>> = vec![None; formula.num_variables + 1];
+    for &lit in model.iter() {
+        let var = lit.unsigned_abs() as usize;
+        if var == 0 || var > formula.num_variables {
+            return Err(invalid_data(format!(
+                "Model literal {} is not a variable of the formula",
+                lit
+            )));
+        }
+        if values[var] == Some(lit < 0) {
+            return Err(invalid_data(format!(
+                "Model assigns variable {} both ways",
+                var
+            )));
+        }
+        values[var] = Some(lit > 0);
+    }
+    let falsified = formula.clauses.iter().find(|clause| {
+        !clause
+            .lits
+            .iter()
+            .any(|&lit| values[lit.unsigned_abs() as usize] == Some(lit > 0))
+    });
+    Ok(match falsified {
+        Some(clause) => Verdict::Falsified(clause.clone()),
+        None => Verdict::Satisfied,
+    })
+}
+
+/// Re-reads `formula_file` and checks the model in `model_file` against it
+pub fn verify_files(formula_file: &str, model_file: &str) -> io::Result<Verdict> {
+    let formula = read_formula(BufReader::new(File::open(formula_file)?))?;
+    let model = read_model(File::open(model_file)?)?;
+    check_model(&formula, &model)
+}
+
+#[cfg(test)]
+mod tests;
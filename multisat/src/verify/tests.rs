@@ -0,0 +1,127 @@
+use super::*;
+use crate::ds::*;
+use crate::{solve_with_assumptions, CNFStatus};
+
+const FORMULA: &str = "c example\np cnf 4 3\n1 -2 0\n2 3\n-4 0\n\n-1 -3 4 0\n";
+
+fn formula() -> InputFormula {
+    read_formula(FORMULA.as_bytes()).unwrap()
+}
+
+#[test]
+fn clauses_keep_the_line_they_start_on() {
+    let formula = formula();
+    assert_eq!(formula.num_variables, 4);
+    let clauses: Vec<(usize, Vec<i32>)> = formula
+        .clauses
+        .iter()
+        .map(|clause| (clause.line, clause.lits.clone()))
+        .collect();
+    assert_eq!(
+        clauses,
+        vec![(3, vec![1, -2]), (4, vec![2, 3, -4]), (7, vec![-1, -3, 4])]
+    );
+}
+
+#[test]
+fn malformed_formulas_are_rejected() {
+    for text in [
+        "1 2 0\np cnf 2 1\n",
+        "p cnf 2 1\n1 3 0\n",
+        "p cnf 2 2\n1 2 0\n",
+        "p cnf 2 1\n1 x 0\n",
+        "p cnf 2\n1 2 0\n",
+        "",
+    ] {
+        assert!(read_formula(text.as_bytes()).is_err(), "{:?}", text);
+    }
+    // a missing final 0 and a trailing % line are accepted
+    assert_eq!(
+        read_formula("p cnf 2 2\n1 0\n-2\n%\n0\n".as_bytes())
+            .unwrap()
+            .clauses
+            .len(),
+        2
+    );
+}
+
+#[test]
+fn first_falsified_clause_is_reported() {
+    let formula = formula();
+    assert_eq!(
+        check_model(&formula, &[1, 2, -3, -4]).unwrap(),
+        Verdict::Satisfied
+    );
+    assert_eq!(
+        check_model(&formula, &[-1, 2, 3, 4]).unwrap(),
+        Verdict::Falsified(InputClause {
+            line: 3,
+            lits: vec![1, -2]
+        })
+    );
+    // a partial model is enough if every clause has a true literal, but
+    // unassigned variables satisfy nothing
+    assert_eq!(
+        check_model(&formula, &[1, 2, -3]).unwrap(),
+        Verdict::Satisfied
+    );
+    assert!(matches!(
+        check_model(&formula, &[1, -2]).unwrap(),
+        Verdict::Falsified(InputClause { line: 4, .. })
+    ));
+}
+
+#[test]
+fn inconsistent_models_are_rejected() {
+    let formula = formula();
+    assert!(check_model(&formula, &[1, -1]).is_err());
+    assert!(check_model(&formula, &[5]).is_err());
+    assert!(check_model(&formula, &[0]).is_err());
+}
+
+#[test]
+fn models_are_read_in_both_formats() {
+    let competition = "s SATISFIABLE\nv 1 -2\nv 3 0\n";
+    assert_eq!(read_model(competition.as_bytes()).unwrap(), vec![1, -2, 3]);
+    let json = "Checking result\n{\"Solution\":\"1 true  2 false  3 true \",\"Instance\":\"f.cnf\",\"Result\":\"SAT\",\"Time\":\"0\"}\n";
+    assert_eq!(read_model(json.as_bytes()).unwrap(), vec![1, -2, 3]);
+    let unsat = "{\"Instance\":\"f.cnf\",\"Result\":\"UNSAT\",\"Time\":\"0\"}\n";
+    assert!(read_model(unsat.as_bytes()).is_err());
+    let broken = "{\"Solution\":\"1 true 2\",\"Result\":\"SAT\"}\n";
+    assert!(read_model(broken.as_bytes()).is_err());
+}
+
+#[test]
+fn solver_models_verify_against_the_input() {
+    let formula = formula();
+    let mut s = SolverState::from_parsed_out(ParsedOut {
+        num_variables: formula.num_variables,
+        num_clauses: formula.clauses.len(),
+        clauses: formula
+            .clauses
+            .iter()
+            .map(|clause| clause.lits.iter().map(|&n| Literal::from(n)).collect())
+            .collect(),
+    });
+    assert!(matches!(s.preprocess(), FormulaPreprocess::Ok));
+    match solve_with_assumptions(&mut s, &[]) {
+        CNFStatus::SAT { model } => {
+            assert_eq!(check_model(&formula, &model).unwrap(), Verdict::Satisfied)
+        }
+        CNFStatus::UNSAT => panic!("formula is satisfiable"),
+    }
+}
+
+#[test]
+fn json_strings_are_escaped_and_read_back() {
+    let value = "a \"quoted\" \\path\\\n\t\u{1}";
+    let quoted = json_quote(value);
+    assert_eq!(quoted, "\"a \\\"quoted\\\" \\\\path\\\\\\n\\t\\u0001\"");
+    let json = format!("{{\"Instance\":{}, \"Result\" : \"SAT\"}}", quoted);
+    assert_eq!(json_field(&json, "Instance").as_deref(), Some(value));
+    assert_eq!(json_field(&json, "Result").as_deref(), Some("SAT"));
+    // a key inside a value is not a field
+    let json = format!("{{\"Instance\":{}}}", json_quote("\"Result\":\"UNSAT\""));
+    assert_eq!(json_field(&json, "Result"), None);
+    assert_eq!(json_field("{\"Result\":\"SAT", "Result"), None);
+}